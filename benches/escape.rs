@@ -0,0 +1,120 @@
+use {
+  boilerplate::{Escaper, Formatter, escaper::Html},
+  criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main},
+  std::{
+    fmt::{self, Write},
+    hint::black_box,
+  },
+};
+
+/// The byte-at-a-time escaping implementation which `Html` replaced.
+struct Baseline<'a> {
+  inner: &'a mut String,
+  indent: &'static str,
+  pending_indent: bool,
+}
+
+impl Write for Baseline<'_> {
+  fn write_str(&mut self, s: &str) -> fmt::Result {
+    let mut chunk_start = 0;
+
+    for (i, &byte) in s.as_bytes().iter().enumerate() {
+      let replacement = match byte {
+        b'"' => Some("&quot;"),
+        b'&' => Some("&amp;"),
+        b'<' => Some("&lt;"),
+        b'>' => Some("&gt;"),
+        b'\'' => Some("&apos;"),
+        _ => None,
+      };
+
+      if byte != b'\n' && replacement.is_none() {
+        continue;
+      }
+
+      if chunk_start < i {
+        self.flush();
+        self.inner.write_str(&s[chunk_start..i])?;
+      }
+
+      if let Some(replacement) = replacement {
+        self.flush();
+        self.inner.write_str(replacement)?;
+      } else {
+        self.inner.write_str("\n")?;
+        self.pending_indent = true;
+      }
+
+      chunk_start = i + 1;
+    }
+
+    if chunk_start < s.len() {
+      self.flush();
+      self.inner.write_str(&s[chunk_start..])?;
+    }
+
+    Ok(())
+  }
+}
+
+impl Baseline<'_> {
+  fn flush(&mut self) {
+    if self.pending_indent {
+      self.inner.push_str(self.indent);
+      self.pending_indent = false;
+    }
+  }
+}
+
+fn inputs() -> Vec<(&'static str, String)> {
+  vec![
+    ("clean", "lorem ipsum dolor sit amet ".repeat(4096)),
+    (
+      "sparse",
+      "<td>lorem ipsum dolor sit amet, consectetur adipiscing elit</td>\n".repeat(2048),
+    ),
+    ("dense", "<a href=\"x\">&'</a>\n".repeat(4096)),
+  ]
+}
+
+fn escape(c: &mut Criterion) {
+  let mut group = c.benchmark_group("escape");
+
+  for (name, input) in inputs() {
+    let mut output = String::with_capacity(input.len() * 4);
+
+    group.throughput(Throughput::Bytes(input.len().try_into().unwrap()));
+
+    group.bench_with_input(BenchmarkId::new("baseline", name), &input, |b, input| {
+      b.iter(|| {
+        output.clear();
+        Baseline {
+          inner: &mut output,
+          indent: "  ",
+          pending_indent: false,
+        }
+        .write_str(black_box(input))
+        .unwrap();
+        black_box(&output);
+      });
+    });
+
+    group.bench_with_input(BenchmarkId::new("html", name), &input, |b, input| {
+      b.iter(|| {
+        output.clear();
+        Html
+          .escape(
+            &mut Formatter::new(&mut output).indent("  "),
+            black_box(input),
+          )
+          .unwrap();
+        black_box(&output);
+      });
+    });
+  }
+
+  group.finish();
+}
+
+criterion_group!(benches, escape);
+criterion_main!(benches);
@@ -3,43 +3,272 @@
 use super::*;
 
 #[derive(FromDeriveInput)]
-#[darling(attributes(boilerplate))]
+#[darling(attributes(boilerplate), supports(struct_any, enum_any))]
 pub(crate) struct Boilerplate {
   axum: Option<bool>,
-  filename: Option<String>,
+  data: Data<Variant, ()>,
+  #[darling(default)]
+  delimiters: Delimiters,
+  error: Option<syn::Path>,
+  escape: Option<escape::Attribute>,
+  filename: Option<LitStr>,
   generics: Generics,
   ident: Ident,
+  interpolation_size_hint: Option<usize>,
+  path: Option<LitStr>,
   text: Option<LitStr>,
 }
 
 impl Boilerplate {
-  pub(crate) fn impls(self) -> TokenStream {
-    let filename = self
+  pub(crate) fn impls(mut self) -> Result<TokenStream, syn::Error> {
+    match mem::replace(
+      &mut self.data,
+      Data::Struct(Fields::new(Style::Unit, Vec::new())),
+    ) {
+      Data::Enum(variants) => self.enum_impls(variants),
+      Data::Struct(_) => {
+        let template = self.template()?;
+        template.impls(template.implementation()?)
+      }
+    }
+  }
+
+  /// Each variant of an enum is rendered with its own template, by a struct
+  /// which borrows the variant's fields. The enum's impls match on the
+  /// variant, and forward to that struct's impls. The enum's text blocks are
+  /// those of each variant in turn, and each variant is rendered with its own
+  /// range of them.
+  fn enum_impls(self, variants: Vec<Variant>) -> Result<TokenStream, syn::Error> {
+    if let Some(attribute) = self
       .filename
-      .unwrap_or_else(|| Self::filename_from_ident(&self.ident.to_string()));
+      .as_ref()
+      .or(self.path.as_ref())
+      .or(self.text.as_ref())
+    {
+      return Err(syn::Error::new(
+        attribute.span(),
+        "enum templates must be set on each variant",
+      ));
+    }
 
-    let source = if let Some(text) = self.text {
-      Source::Literal(text)
-    } else {
-      let manifest_dir = std::env::var("CARGO_MANIFEST_DIR")
-        .expect("Failed to get `CARGO_MANIFEST_DIR` environment variable");
+    if variants.is_empty() {
+      return Err(syn::Error::new(
+        self.ident.span(),
+        "enums without variants cannot be templates",
+      ));
+    }
+
+    let ident = &self.ident;
+    let (impl_generics, ty_generics, where_clause) = self.generics.split_for_impl();
+
+    let mut arms = Vec::new();
+    let mut definitions = Vec::new();
+    let mut html_safe = true;
+    let mut ranges = Vec::new();
+    let mut size_hint = 0;
+    let mut text = Vec::new();
+
+    for variant in &variants {
+      let template = Self {
+        axum: self.axum,
+        data: Data::Struct(Fields::new(Style::Unit, Vec::new())),
+        delimiters: variant
+          .delimiters
+          .clone()
+          .unwrap_or_else(|| self.delimiters.clone()),
+        error: self.error.clone(),
+        escape: variant.escape.clone().or_else(|| self.escape.clone()),
+        filename: if variant.path.is_some() {
+          None
+        } else {
+          Some(variant.filename.clone().unwrap_or_else(|| {
+            LitStr::new(
+              &Self::filename_from_ident(&variant.ident.to_string()),
+              variant.ident.span(),
+            )
+          }))
+        },
+        generics: Variant::generics(&self.generics),
+        ident: variant.struct_ident(ident),
+        interpolation_size_hint: variant
+          .interpolation_size_hint
+          .or(self.interpolation_size_hint),
+        path: variant.path.clone(),
+        text: variant.text.clone(),
+      }
+      .template()?;
+
+      html_safe &= matches!(template.escape, Some(Escape::Html));
+
+      arms.push((
+        variant.pattern(),
+        variant.constructor(&template.ident, &self.generics),
+      ));
+
+      let implementation = template.implementation()?;
 
-      let path = Path::new(&manifest_dir).join("templates").join(&filename);
+      size_hint = size_hint.max(implementation.size_hint(template.interpolation_size_hint));
 
-      let path = path.to_str().unwrap_or_else(|| {
-        panic!(
-          "Path to template `{}` was not valid unicode",
-          path.display(),
-        )
+      ranges.push((text.len(), text.len() + implementation.text.len()));
+
+      text.extend(implementation.text.iter().cloned());
+
+      definitions.push(variant.definition(&template.ident, &self.generics));
+      definitions.push(template.impls(implementation)?);
+    }
+
+    let boilerplate_arms = arms
+      .iter()
+      .zip(ranges)
+      .map(|((pattern, constructor), (start, end))| {
+        quote! {
+          #pattern => ::boilerplate::Boilerplate::boilerplate(
+            &#constructor,
+            boilerplate_text.get(#start..#end).ok_or(::core::fmt::Error)?,
+            boilerplate_output,
+          ),
+        }
       });
 
-      Source::Path(path.into())
-    };
+    let reload = cfg!(feature = "reload").then(|| {
+      let delimiters = &self.delimiters;
+      let root = templates(self.ident.span())?.to_string_lossy().into_owned();
+      Ok::<TokenStream, syn::Error>(quote! {
+        const DELIMITERS: ::boilerplate::Delimiters<'static> = #delimiters;
+        const INCLUDES: &'static [(&'static str, &'static str)] = &[];
+        const PATH: Option<&'static str> = None;
+        const ROOT: &'static str = #root;
+      })
+    });
+
+    let reload = reload.transpose()?;
+
+    let fallible_impl = self.error.as_ref().map(|error| {
+      let arms = arms
+        .iter()
+        .map(|(pattern, constructor)| quote!(#pattern => #constructor.try_render(),));
+
+      quote! {
+        extern crate alloc;
+
+        impl #impl_generics #ident #ty_generics #where_clause {
+          /// Render the variant's template to a new `String`, returning
+          /// errors from template code.
+          pub fn try_render(&self) -> ::core::result::Result<alloc::string::String, #error> {
+            match self {
+              #(#arms)*
+            }
+          }
+        }
+      }
+    });
+
+    let html_safe_impl = html_safe.then(|| {
+      quote! {
+        impl #impl_generics ::boilerplate::HtmlSafe for #ident #ty_generics #where_clause {}
+      }
+    });
+
+    let axum_into_response_impl = self.axum.unwrap_or(cfg!(feature = "axum")).then(|| {
+      let arms = arms.iter().map(|(pattern, constructor)| {
+        quote!(#pattern => ::axum::response::IntoResponse::into_response(#constructor),)
+      });
 
-    let escape = Path::new(&filename).extension().is_some_and(|extension| {
-      ["html", "htm", "xml"].contains(&extension.to_string_lossy().as_ref())
+      quote! {
+        impl #impl_generics ::axum::response::IntoResponse for #ident #ty_generics #where_clause {
+          fn into_response(self) -> ::axum::response::Response {
+            match &self {
+              #(#arms)*
+            }
+          }
+        }
+      }
     });
 
+    let track_templates = track_templates();
+
+    Ok(quote! {
+      const _: () = {
+        #(#definitions)*
+
+        impl #impl_generics ::boilerplate::Boilerplate for #ident #ty_generics #where_clause {
+          const TEMPLATE: &'static str = "";
+
+          const TEXT: &'static [&'static str] = &[ #(#text),* ];
+
+          const SIZE_HINT: usize = #size_hint;
+
+          #reload
+
+          fn boilerplate(
+            &self,
+            boilerplate_text: &[impl ::core::convert::AsRef<str>],
+            boilerplate_output: &mut dyn ::core::fmt::Write,
+          ) -> ::core::fmt::Result {
+            match self {
+              #(#boilerplate_arms)*
+            }
+          }
+        }
+
+        impl #impl_generics ::core::fmt::Display for #ident #ty_generics #where_clause {
+          fn fmt(&self, boilerplate_output: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+            <Self as ::boilerplate::Boilerplate>::boilerplate(
+              self,
+              <Self as ::boilerplate::Boilerplate>::TEXT,
+              boilerplate_output,
+            )
+          }
+        }
+
+        #fallible_impl
+
+        #html_safe_impl
+
+        #axum_into_response_impl
+
+        #track_templates
+      };
+    })
+  }
+
+  fn template(self) -> Result<Template, syn::Error> {
+    if let Some(path) = &self.path
+      && (self.filename.is_some() || self.text.is_some())
+    {
+      return Err(syn::Error::new(
+        path.span(),
+        "`path` may not be used with `filename` or `text`",
+      ));
+    }
+
+    let filename = self.path.as_ref().or(self.filename.as_ref()).map_or_else(
+      || Self::filename_from_ident(&self.ident.to_string()),
+      LitStr::value,
+    );
+
+    let span = self
+      .path
+      .as_ref()
+      .or(self.filename.as_ref())
+      .map_or_else(|| self.ident.span(), LitStr::span);
+
+    let source = if let Some(text) = self.text {
+      Source::Literal(text)
+    } else if self.path.is_some() {
+      Source::from_path(&manifest_dir(span)?.join(&filename), span)?
+    } else {
+      Source::from_path(&templates(self.ident.span())?.join(&filename), span)?
+    };
+
+    let escape = Escape::new(
+      self.escape,
+      &Path::new(&filename)
+        .extension()
+        .unwrap_or_default()
+        .to_string_lossy(),
+    );
+
     let guess = new_mime_guess::from_path(&filename).first_or_text_plain();
 
     let mime = if guess.type_() == mime::TEXT && guess.get_param(mime::CHARSET).is_none() {
@@ -48,15 +277,19 @@ impl Boilerplate {
       guess
     };
 
-    Template {
+    Ok(Template {
       axum: self.axum,
+      delimiters: self.delimiters,
+      error: self.error,
       escape,
       generics: self.generics,
       ident: self.ident,
+      interpolation_size_hint: self
+        .interpolation_size_hint
+        .unwrap_or(Implementation::INTERPOLATION_SIZE_HINT),
       mime,
       source,
-    }
-    .impls()
+    })
   }
 
   fn filename_from_ident(ident: &str) -> String {
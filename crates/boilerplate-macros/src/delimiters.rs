@@ -0,0 +1,125 @@
+use super::*;
+
+/// Block delimiters set with `#[boilerplate(delimiters(...))]`.
+///
+/// Blocks are given as an open and close delimiter separated by whitespace,
+/// for example `code = "<% %>"`, and line blocks as an open delimiter, for
+/// example `code_line = "%"`. Empty strings disable a block, and omitted
+/// blocks use the default delimiters.
+#[derive(Clone, Default, FromMeta)]
+#[darling(and_then = Self::validate)]
+pub(crate) struct Delimiters {
+  code: Option<Pair>,
+  code_line: Option<String>,
+  comment: Option<Pair>,
+  comment_line: Option<String>,
+  interpolation: Option<Pair>,
+  interpolation_line: Option<String>,
+}
+
+impl Delimiters {
+  pub(crate) fn get(&self) -> boilerplate_parser::Delimiters<'_> {
+    let default = boilerplate_parser::Delimiters::DEFAULT;
+
+    boilerplate_parser::Delimiters {
+      code: self.code.as_ref().map_or(default.code, Pair::get),
+      code_line: self.code_line.as_deref().unwrap_or(default.code_line),
+      comment: self.comment.as_ref().map_or(default.comment, Pair::get),
+      comment_line: self.comment_line.as_deref().unwrap_or(default.comment_line),
+      interpolation: self
+        .interpolation
+        .as_ref()
+        .map_or(default.interpolation, Pair::get),
+      interpolation_line: self
+        .interpolation_line
+        .as_deref()
+        .unwrap_or(default.interpolation_line),
+    }
+  }
+
+  fn validate(self) -> darling::Result<Self> {
+    let delimiters = self.get();
+
+    let open = [
+      ("code", delimiters.code.0),
+      ("code_line", delimiters.code_line),
+      ("comment", delimiters.comment.0),
+      ("comment_line", delimiters.comment_line),
+      ("interpolation", delimiters.interpolation.0),
+      ("interpolation_line", delimiters.interpolation_line),
+    ];
+
+    for (i, (a, delimiter)) in open.iter().enumerate() {
+      if delimiter.is_empty() {
+        continue;
+      }
+
+      if let Some((b, _)) = open[i + 1..].iter().find(|(_, other)| other == delimiter) {
+        return Err(darling::Error::custom(format!(
+          "`{a}` and `{b}` have the same open delimiter `{delimiter}`",
+        )));
+      }
+    }
+
+    Ok(self)
+  }
+}
+
+impl ToTokens for Delimiters {
+  fn to_tokens(&self, tokens: &mut TokenStream) {
+    let boilerplate_parser::Delimiters {
+      code: (code_open, code_close),
+      code_line,
+      comment: (comment_open, comment_close),
+      comment_line,
+      interpolation: (interpolation_open, interpolation_close),
+      interpolation_line,
+    } = self.get();
+
+    tokens.extend(quote! {
+      ::boilerplate::Delimiters {
+        code: (#code_open, #code_close),
+        code_line: #code_line,
+        comment: (#comment_open, #comment_close),
+        comment_line: #comment_line,
+        interpolation: (#interpolation_open, #interpolation_close),
+        interpolation_line: #interpolation_line,
+      }
+    });
+  }
+}
+
+#[derive(Clone)]
+struct Pair {
+  close: String,
+  open: String,
+}
+
+impl Pair {
+  fn get(&self) -> (&str, &str) {
+    (&self.open, &self.close)
+  }
+}
+
+impl FromMeta for Pair {
+  fn from_string(value: &str) -> darling::Result<Self> {
+    if value.is_empty() {
+      return Ok(Self {
+        close: String::new(),
+        open: String::new(),
+      });
+    }
+
+    let mut words = value.split_whitespace();
+
+    match (words.next(), words.next(), words.next()) {
+      (Some(open), Some(close), None) => Ok(Self {
+        close: close.into(),
+        open: open.into(),
+      }),
+      _ => Err(darling::Error::custom(
+        "expected open and close delimiters separated by whitespace",
+      )),
+    }
+  }
+}
@@ -0,0 +1,90 @@
+use super::*;
+
+/// How interpolations in a template are escaped.
+pub(crate) enum Escape {
+  /// Escape interpolations with the escaper for their context in the
+  /// surrounding HTML.
+  Html,
+  /// Escape all interpolations with an escaper.
+  Escaper(syn::Path),
+}
+
+impl Escape {
+  pub(crate) fn from_extension(extension: &str) -> Option<Self> {
+    let escaper = match extension {
+      "htm" | "html" => return Some(Self::Html),
+      "css" => quote!(Css),
+      "js" => quote!(Js),
+      "json" => quote!(Json),
+      "sh" => quote!(Sh),
+      "tex" => quote!(Tex),
+      "xml" => quote!(Html),
+      _ => return None,
+    };
+
+    Some(Self::Escaper(syn::parse_quote!(
+      ::boilerplate::escaper::#escaper
+    )))
+  }
+
+  /// Resolve `#[boilerplate(escape = ...)]`, if given, against the escaping
+  /// guessed from the template's extension. `true` uses the guess, falling
+  /// back to HTML escaping if there is none.
+  pub(crate) fn new(attribute: Option<Attribute>, extension: &str) -> Option<Self> {
+    match attribute {
+      None => Self::from_extension(extension),
+      Some(Attribute::Enabled(false)) => None,
+      Some(Attribute::Enabled(true)) => Some(
+        Self::from_extension(extension)
+          .unwrap_or_else(|| Self::Escaper(syn::parse_quote!(::boilerplate::escaper::Html))),
+      ),
+      Some(Attribute::Escaper(path)) => Some(Self::Escaper(path)),
+      Some(Attribute::Extension(extension)) => Self::from_extension(&extension),
+    }
+  }
+}
+
+/// Escaping set with `#[boilerplate(escape = ...)]`, either `true`, `false`,
+/// the path to an escaper, or a string containing an extension, like `"html"`,
+/// whose escaping is used.
+#[derive(Clone)]
+pub(crate) enum Attribute {
+  Enabled(bool),
+  Escaper(syn::Path),
+  Extension(String),
+}
+
+impl FromMeta for Attribute {
+  fn from_bool(value: bool) -> darling::Result<Self> {
+    Ok(Self::Enabled(value))
+  }
+
+  fn from_string(value: &str) -> darling::Result<Self> {
+    if Escape::from_extension(value).is_none() {
+      return Err(darling::Error::custom(format!(
+        "no escaping for extension `{value}`"
+      )));
+    }
+
+    Ok(Self::Extension(value.into()))
+  }
+
+  fn from_expr(expr: &syn::Expr) -> darling::Result<Self> {
+    match expr {
+      syn::Expr::Lit(syn::ExprLit {
+        lit: syn::Lit::Bool(value),
+        ..
+      }) => Self::from_bool(value.value),
+      syn::Expr::Lit(syn::ExprLit {
+        lit: syn::Lit::Str(value),
+        ..
+      }) => Self::from_string(&value.value()),
+      syn::Expr::Path(syn::ExprPath {
+        path, qself: None, ..
+      }) => Ok(Self::Escaper(path.clone())),
+      _ => Err(darling::Error::custom(
+        "expected `true`, `false`, an extension, or the path to an escaper",
+      )),
+    }
+  }
+}
@@ -0,0 +1,250 @@
+use super::*;
+
+/// Filters provided by `boilerplate::filters`.
+const BUILTIN: &[&str] = &[
+  "css",
+  "html",
+  "js",
+  "json",
+  "lower",
+  "sh",
+  "tex",
+  "trim",
+  "truncate",
+  "upper",
+  "url",
+  "urlencode",
+];
+
+/// A filter applied to an interpolation, `name` or `name(args...)`.
+struct Filter {
+  args: Vec<Expr>,
+  name: Ident,
+}
+
+impl Filter {
+  fn parse(tokens: TokenStream) -> Result<Self, syn::Error> {
+    let expr = syn::parse2::<Expr>(tokens)?;
+
+    let (path, args) = match &expr {
+      Expr::Path(path) => (path, Vec::new()),
+      Expr::Call(call) if call.attrs.is_empty() => match &*call.func {
+        Expr::Path(path) => (path, call.args.iter().cloned().collect()),
+        _ => return Err(Self::error(&expr)),
+      },
+      _ => return Err(Self::error(&expr)),
+    };
+
+    if !path.attrs.is_empty() || path.qself.is_some() {
+      return Err(Self::error(&expr));
+    }
+
+    let Some(name) = path.path.get_ident() else {
+      return Err(Self::error(&expr));
+    };
+
+    Ok(Self {
+      args,
+      name: name.clone(),
+    })
+  }
+
+  fn error(expr: &Expr) -> syn::Error {
+    syn::Error::new_spanned(
+      expr,
+      "expected filter name, optionally followed by arguments, like `upper` or `truncate(10)`",
+    )
+  }
+}
+
+/// An interpolation with filters, `{{ value |> filter |> filter(args...) }}`.
+///
+/// Since `|>` is not a Rust operator, filters are found by splitting the
+/// interpolation on `|>` tokens outside of delimiters.
+pub(crate) struct Filters {
+  filters: Vec<Filter>,
+  value: Expr,
+}
+
+impl Filters {
+  pub(crate) fn parse(code: &str) -> Result<Self, syn::Error> {
+    let mut segments = vec![TokenStream::new()];
+
+    let mut tokens = syn::parse_str::<TokenStream>(code)?.into_iter().peekable();
+
+    while let Some(tree) = tokens.next() {
+      if let TokenTree::Punct(punct) = &tree
+        && punct.as_char() == '|'
+        && punct.spacing() == Spacing::Joint
+        && let Some(TokenTree::Punct(next)) = tokens.peek()
+        && next.as_char() == '>'
+      {
+        tokens.next();
+        segments.push(TokenStream::new());
+        continue;
+      }
+
+      segments.last_mut().unwrap().extend([tree]);
+    }
+
+    let mut segments = segments.into_iter();
+
+    let value = syn::parse2::<Expr>(segments.next().unwrap())?;
+
+    let filters = segments
+      .map(Filter::parse)
+      .collect::<Result<Vec<Filter>, syn::Error>>()?;
+
+    for (i, filter) in filters.iter().enumerate() {
+      let name = filter.name.to_string();
+
+      let arguments = match name.as_str() {
+        "truncate" => 1,
+        "trusted" => {
+          if i + 1 < filters.len() {
+            return Err(syn::Error::new(
+              filter.name.span(),
+              "`trusted` must be the last filter",
+            ));
+          }
+          0
+        }
+        name if BUILTIN.contains(&name) => 0,
+        _ => continue,
+      };
+
+      if filter.args.len() != arguments {
+        return Err(syn::Error::new(
+          filter.name.span(),
+          format!(
+            "filter `{name}` takes {arguments} argument{}, but {} were given",
+            if arguments == 1 { "" } else { "s" },
+            filter.args.len(),
+          ),
+        ));
+      }
+    }
+
+    Ok(Self { filters, value })
+  }
+
+  /// Lower the interpolation to an expression which applies each filter in
+  /// turn. Built-in filters are called from `boilerplate::filters`, and all
+  /// others from a `filters` module in scope where the template is used.
+  ///
+  /// Returns the expression, and whether the value is trusted, in which case
+  /// it should not be escaped.
+  pub(crate) fn lower(code: &str) -> Result<(String, bool), syn::Error> {
+    let Self { filters, value } = Self::parse(code)?;
+
+    if filters.is_empty() {
+      return Ok((code.into(), false));
+    }
+
+    let mut lowered = quote!(&(#value));
+    let mut trusted = false;
+
+    for Filter { args, name } in filters {
+      lowered = if name == "trusted" {
+        trusted = true;
+        lowered
+      } else if BUILTIN.contains(&name.to_string().as_str()) {
+        quote!(::boilerplate::filters::#name(#lowered #(, #args)*))
+      } else {
+        quote!(filters::#name(#lowered #(, #args)*))
+      };
+    }
+
+    Ok((lowered.to_string(), trusted))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[track_caller]
+  fn case(code: &str, expected: TokenStream, trusted: bool) {
+    let (lowered, actual) = Filters::lower(code).unwrap();
+    assert_eq!(lowered, expected.to_string());
+    assert_eq!(actual, trusted);
+  }
+
+  #[track_caller]
+  fn error(code: &str, expected: &str) {
+    assert_eq!(Filters::parse(code).err().unwrap().to_string(), expected,);
+  }
+
+  #[test]
+  fn lower() {
+    assert_eq!(
+      Filters::lower("self.name").unwrap(),
+      ("self.name".into(), false),
+    );
+    case(
+      "self.name |> upper",
+      quote!(::boilerplate::filters::upper(&(self.name))),
+      false,
+    );
+    case(
+      "self.name |> trim |> truncate(10)",
+      quote!(::boilerplate::filters::truncate(
+        ::boilerplate::filters::trim(&(self.name)),
+        10
+      )),
+      false,
+    );
+    case("self.body |> trusted", quote!(&(self.body)), true);
+    case(
+      "self.body |> lower |> trusted",
+      quote!(::boilerplate::filters::lower(&(self.body))),
+      true,
+    );
+    case(
+      "self.name |> shout(3)",
+      quote!(filters::shout(&(self.name), 3)),
+      false,
+    );
+    case(
+      "a | b |> json",
+      quote!(::boilerplate::filters::json(&(a | b))),
+      false,
+    );
+  }
+
+  #[test]
+  fn not_filters() {
+    #[track_caller]
+    fn case(code: &str) {
+      assert_eq!(Filters::lower(code).unwrap(), (code.into(), false));
+    }
+
+    case("a | b");
+    case("a | upper");
+    case("|x| x > 1");
+    case("f(|x| x)");
+  }
+
+  #[test]
+  fn errors() {
+    error("x |> trusted |> upper", "`trusted` must be the last filter");
+    error(
+      "x |> truncate",
+      "filter `truncate` takes 1 argument, but 0 were given",
+    );
+    error(
+      "x |> upper(1)",
+      "filter `upper` takes 0 arguments, but 1 were given",
+    );
+    error(
+      "x |> b::c",
+      "expected filter name, optionally followed by arguments, like `upper` or `truncate(10)`",
+    );
+    error(
+      "x |> 1",
+      "expected filter name, optionally followed by arguments, like `upper` or `truncate(10)`",
+    );
+    error("x |>", "unexpected end of input, expected an expression");
+    error("{", "cannot parse string into token stream");
+  }
+}
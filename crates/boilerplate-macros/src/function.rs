@@ -0,0 +1,194 @@
+use super::*;
+
+/// Input to the function-like `boilerplate!` macro: the template, either a
+/// string literal, or `file = "path"`, relative to `templates`, and optionally
+/// an extension, like `html`, whose escaping should be used, or
+/// `escape = ...`, which accepts the same values as
+/// `#[boilerplate(escape = ...)]`. Templates read from files are escaped
+/// according to their extension by default.
+pub(crate) struct Function {
+  pub(crate) escape: Option<Escape>,
+  pub(crate) source: Source,
+}
+
+impl Parse for Function {
+  fn parse(input: ParseStream) -> syn::Result<Self> {
+    fn duplicate(present: bool, span: Span, name: &str) -> syn::Result<()> {
+      if present {
+        return Err(syn::Error::new(span, format!("duplicate {name}")));
+      }
+      Ok(())
+    }
+
+    let mut escape = None;
+    let mut file = None::<LitStr>;
+    let mut template = None::<LitStr>;
+
+    while !input.is_empty() {
+      if input.peek(LitStr) {
+        let literal = input.parse::<LitStr>()?;
+        duplicate(template.is_some(), literal.span(), "template")?;
+        template = Some(literal);
+      } else {
+        let ident = input.parse::<Ident>()?;
+
+        if input.peek(syn::Token![=]) {
+          input.parse::<syn::Token![=]>()?;
+
+          if ident == "escape" {
+            duplicate(escape.is_some(), ident.span(), "escaping option")?;
+            escape = Some(
+              escape::Attribute::from_expr(&input.parse()?)
+                .map_err(|err| syn::Error::new(ident.span(), err))?,
+            );
+          } else if ident == "file" {
+            duplicate(file.is_some(), ident.span(), "`file`")?;
+            file = Some(input.parse()?);
+          } else {
+            return Err(syn::Error::new(
+              ident.span(),
+              format!("unknown option `{ident}`"),
+            ));
+          }
+        } else {
+          duplicate(escape.is_some(), ident.span(), "escaping option")?;
+          escape = Some(
+            escape::Attribute::from_string(&ident.to_string())
+              .map_err(|err| syn::Error::new(ident.span(), err))?,
+          );
+        }
+      }
+
+      if !input.is_empty() {
+        input.parse::<syn::Token![,]>()?;
+      }
+    }
+
+    let (source, extension) = match (template, file) {
+      (Some(template), None) => (Source::Literal(template), String::new()),
+      (None, Some(file)) => {
+        let filename = file.value();
+        (
+          Source::from_path(&templates(file.span())?.join(&filename), file.span())?,
+          Path::new(&filename)
+            .extension()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned(),
+        )
+      }
+      (Some(template), Some(_)) => {
+        return Err(syn::Error::new(
+          template.span(),
+          "template may not be given as both a string and a `file`",
+        ));
+      }
+      (None, None) => {
+        return Err(input.error("expected template string or `file = \"...\"`"));
+      }
+    };
+
+    Ok(Self {
+      escape: Escape::new(escape, &extension),
+      source,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[track_caller]
+  fn parse(tokens: TokenStream) -> Function {
+    syn::parse2(tokens).unwrap()
+  }
+
+  #[track_caller]
+  fn error(tokens: TokenStream, message: &str) {
+    assert_eq!(
+      syn::parse2::<Function>(tokens).err().unwrap().to_string(),
+      message,
+    );
+  }
+
+  #[test]
+  fn template() {
+    let function = parse(quote!("foo"));
+    assert!(function.escape.is_none());
+    assert!(matches!(function.source, Source::Literal(literal) if literal.value() == "foo"));
+  }
+
+  #[test]
+  fn trailing_comma() {
+    assert!(matches!(parse(quote!("foo",)).source, Source::Literal(_)));
+  }
+
+  #[test]
+  fn extension() {
+    assert!(matches!(parse(quote!(html, "")).escape, Some(Escape::Html)));
+    assert!(matches!(
+      parse(quote!(json, "")).escape,
+      Some(Escape::Escaper(_)),
+    ));
+  }
+
+  #[test]
+  fn escape() {
+    assert!(matches!(
+      parse(quote!(escape = "html", "")).escape,
+      Some(Escape::Html),
+    ));
+    assert!(matches!(
+      parse(quote!(escape = true, "")).escape,
+      Some(Escape::Escaper(_)),
+    ));
+    assert!(parse(quote!(escape = false, "")).escape.is_none());
+    assert!(matches!(
+      parse(quote!(escape = foo::Bar, "")).escape,
+      Some(Escape::Escaper(_)),
+    ));
+  }
+
+  #[test]
+  fn file() {
+    let function = parse(quote!(file = "foo/bar.html"));
+
+    assert!(matches!(function.escape, Some(Escape::Html)));
+
+    let Source::Path { path, .. } = function.source else {
+      panic!("expected path");
+    };
+
+    assert!(Path::new(&path).ends_with("templates/foo/bar.html"));
+
+    assert!(parse(quote!(file = "foo.txt")).escape.is_none());
+    assert!(
+      parse(quote!(escape = false, file = "foo.html"))
+        .escape
+        .is_none()
+    );
+  }
+
+  #[test]
+  fn errors() {
+    error(quote!(txt, ""), "no escaping for extension `txt`");
+    error(
+      quote!(escape = "txt", ""),
+      "no escaping for extension `txt`",
+    );
+    error(quote!(foo = true, ""), "unknown option `foo`");
+    error(quote!(html ""), "expected `,`");
+    error(quote!("" ""), "expected `,`");
+    error(quote!("", ""), "duplicate template");
+    error(quote!(html, json, ""), "duplicate escaping option");
+    error(
+      quote!("", file = "foo"),
+      "template may not be given as both a string and a `file`",
+    );
+    error(
+      quote!(html),
+      "unexpected end of input, expected template string or `file = \"...\"`",
+    );
+  }
+}
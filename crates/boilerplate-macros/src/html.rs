@@ -0,0 +1,500 @@
+use super::*;
+
+/// The context of an interpolation in HTML, which determines how it is
+/// escaped.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Context {
+  Css,
+  /// Attribute values, and other contexts which are escaped as HTML.
+  Html,
+  Js,
+  /// Element content, where values implementing `HtmlSafe` are not escaped.
+  Text,
+  Url,
+}
+
+impl Context {
+  /// Path to the escaper for this context.
+  pub(crate) fn escaper(self) -> &'static str {
+    match self {
+      Self::Css => "::boilerplate::escaper::Css",
+      Self::Html | Self::Text => "::boilerplate::escaper::Html",
+      Self::Js => "::boilerplate::escaper::Js",
+      Self::Url => "::boilerplate::escaper::Url",
+    }
+  }
+}
+
+/// Attributes whose values are URLs.
+const URL_ATTRIBUTES: &[&str] = &[
+  "action",
+  "background",
+  "cite",
+  "codebase",
+  "data",
+  "formaction",
+  "href",
+  "icon",
+  "longdesc",
+  "manifest",
+  "poster",
+  "src",
+  "xlink:href",
+];
+
+/// Elements whose content is not parsed as HTML.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Element {
+  Script,
+  Style,
+  Textarea,
+  Title,
+}
+
+impl Element {
+  fn name(self) -> &'static str {
+    match self {
+      Self::Script => "script",
+      Self::Style => "style",
+      Self::Textarea => "textarea",
+      Self::Title => "title",
+    }
+  }
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Tag {
+  end: bool,
+  name: String,
+}
+
+impl Tag {
+  /// The state after the tag is closed with `>`.
+  fn close(self) -> State {
+    let element = match self.name.as_str() {
+      "script" => Element::Script,
+      "style" => Element::Style,
+      "textarea" => Element::Textarea,
+      "title" => Element::Title,
+      _ => return State::Text,
+    };
+
+    if self.end {
+      State::Text
+    } else {
+      State::RawText {
+        comment: None,
+        element,
+        string: None,
+      }
+    }
+  }
+}
+
+#[derive(Debug, Default, PartialEq)]
+enum State {
+  AfterAttributeName {
+    attribute: String,
+    tag: Tag,
+  },
+  AttributeName {
+    attribute: String,
+    tag: Tag,
+  },
+  BeforeValue {
+    attribute: String,
+    tag: Tag,
+  },
+  Comment,
+  RawText {
+    /// terminator of the current comment, if in a comment
+    comment: Option<&'static str>,
+    element: Element,
+    /// quote of the current string literal, if in a string literal
+    string: Option<char>,
+  },
+  Tag(Tag),
+  TagName(Tag),
+  #[default]
+  Text,
+  Value {
+    attribute: String,
+    /// whether the value is empty so far
+    empty: bool,
+    quote: Option<char>,
+    tag: Tag,
+  },
+}
+
+/// Tracks the HTML context of interpolations, by scanning the text which
+/// precedes them.
+///
+/// Templates are scanned linearly, so the text of all branches of conditionals
+/// are considered, in order.
+#[derive(Debug, Default)]
+pub(crate) struct Html {
+  state: State,
+}
+
+impl Html {
+  /// Get the context of an interpolation at the current position.
+  pub(crate) fn interpolation(&mut self) -> Result<Context, String> {
+    match &mut self.state {
+      State::Text => Ok(Context::Text),
+      State::Comment
+      | State::RawText {
+        element: Element::Textarea | Element::Title,
+        ..
+      } => Ok(Context::Html),
+      State::RawText {
+        element: Element::Script,
+        string: Some('"' | '\''),
+        ..
+      } => Ok(Context::Js),
+      State::RawText {
+        element: Element::Style,
+        string: Some(_),
+        ..
+      } => Ok(Context::Css),
+      State::RawText { element, .. } => Err(format!(
+        "interpolations in `<{}>` elements must be inside of quoted string literals",
+        element.name(),
+      )),
+      State::AfterAttributeName { .. }
+      | State::AttributeName { .. }
+      | State::Tag(_)
+      | State::TagName(_) => {
+        Err("interpolations in HTML tags must be inside of quoted attribute values".into())
+      }
+      State::BeforeValue { .. } | State::Value { quote: None, .. } => {
+        Err("interpolations in unquoted attribute values are not supported".into())
+      }
+      State::Value {
+        attribute, empty, ..
+      } => {
+        if attribute.starts_with("on") {
+          return Err(format!(
+            "interpolations in event handler attribute `{attribute}` are not supported"
+          ));
+        }
+
+        if attribute == "style" {
+          return Err("interpolations in `style` attributes are not supported".into());
+        }
+
+        let context = if *empty && URL_ATTRIBUTES.contains(&attribute.as_str()) {
+          Context::Url
+        } else {
+          Context::Html
+        };
+
+        *empty = false;
+
+        Ok(context)
+      }
+    }
+  }
+
+  /// Advance past `text`.
+  pub(crate) fn text(&mut self, text: &str) {
+    let mut rest = text;
+
+    while !rest.is_empty() {
+      let (state, len) = Self::step(mem::take(&mut self.state), rest);
+      self.state = state;
+      rest = &rest[len..];
+    }
+  }
+
+  /// Advance past the start of `rest`, returning the new state and the number
+  /// of bytes consumed.
+  fn step(state: State, rest: &str) -> (State, usize) {
+    let c = rest.chars().next().unwrap();
+    let len = c.len_utf8();
+
+    let tag_start = |prefix: &str| {
+      rest
+        .strip_prefix(prefix)
+        .is_some_and(|rest| rest.starts_with(|c: char| c.is_ascii_alphabetic()))
+    };
+
+    let state = match state {
+      State::Text => {
+        if rest.starts_with("<!--") {
+          return (State::Comment, 4);
+        }
+
+        if tag_start("</") {
+          return (
+            State::TagName(Tag {
+              end: true,
+              name: String::new(),
+            }),
+            2,
+          );
+        }
+
+        if tag_start("<") {
+          State::TagName(Tag::default())
+        } else {
+          State::Text
+        }
+      }
+      State::Comment => {
+        if rest.starts_with("-->") {
+          return (State::Text, 3);
+        }
+        State::Comment
+      }
+      State::TagName(mut tag) => match c {
+        '>' => tag.close(),
+        '/' => State::Tag(tag),
+        c if c.is_ascii_whitespace() => State::Tag(tag),
+        c => {
+          tag.name.push(c.to_ascii_lowercase());
+          State::TagName(tag)
+        }
+      },
+      State::Tag(tag) => match c {
+        '>' => tag.close(),
+        '/' => State::Tag(tag),
+        c if c.is_ascii_whitespace() => State::Tag(tag),
+        c => State::AttributeName {
+          attribute: c.to_ascii_lowercase().into(),
+          tag,
+        },
+      },
+      State::AttributeName { mut attribute, tag } => match c {
+        '>' => tag.close(),
+        '/' => State::Tag(tag),
+        '=' => State::BeforeValue { attribute, tag },
+        c if c.is_ascii_whitespace() => State::AfterAttributeName { attribute, tag },
+        c => {
+          attribute.push(c.to_ascii_lowercase());
+          State::AttributeName { attribute, tag }
+        }
+      },
+      State::AfterAttributeName { attribute, tag } => match c {
+        '>' => tag.close(),
+        '/' => State::Tag(tag),
+        '=' => State::BeforeValue { attribute, tag },
+        c if c.is_ascii_whitespace() => State::AfterAttributeName { attribute, tag },
+        c => State::AttributeName {
+          attribute: c.to_ascii_lowercase().into(),
+          tag,
+        },
+      },
+      State::BeforeValue { attribute, tag } => match c {
+        '>' => tag.close(),
+        '"' | '\'' => State::Value {
+          attribute,
+          empty: true,
+          quote: Some(c),
+          tag,
+        },
+        c if c.is_ascii_whitespace() => State::BeforeValue { attribute, tag },
+        _ => State::Value {
+          attribute,
+          empty: false,
+          quote: None,
+          tag,
+        },
+      },
+      State::Value {
+        attribute,
+        quote: Some(quote),
+        tag,
+        ..
+      } => {
+        if c == quote {
+          State::Tag(tag)
+        } else {
+          State::Value {
+            attribute,
+            empty: false,
+            quote: Some(quote),
+            tag,
+          }
+        }
+      }
+      State::Value {
+        attribute,
+        quote: None,
+        tag,
+        ..
+      } => match c {
+        '>' => tag.close(),
+        c if c.is_ascii_whitespace() => State::Tag(tag),
+        _ => State::Value {
+          attribute,
+          empty: false,
+          quote: None,
+          tag,
+        },
+      },
+      State::RawText {
+        mut comment,
+        element,
+        mut string,
+      } => {
+        // the end tag closes the element, even inside of a string or comment
+        if rest
+          .get(..element.name().len() + 2)
+          .is_some_and(|prefix| prefix.eq_ignore_ascii_case(&format!("</{}", element.name())))
+        {
+          return (
+            State::TagName(Tag {
+              end: true,
+              name: String::new(),
+            }),
+            2,
+          );
+        }
+
+        let code = matches!(element, Element::Script | Element::Style);
+
+        let len = if let Some(terminator) = comment {
+          if rest.starts_with(terminator) {
+            comment = None;
+            terminator.len()
+          } else {
+            len
+          }
+        } else if let Some(quote) = string {
+          if c == '\\' {
+            len + rest[len..].chars().next().map_or(0, char::len_utf8)
+          } else {
+            if c == quote || (c == '\n' && quote != '`') {
+              string = None;
+            }
+            len
+          }
+        } else if code && rest.starts_with("/*") {
+          comment = Some("*/");
+          2
+        } else if element == Element::Script && rest.starts_with("//") {
+          comment = Some("\n");
+          2
+        } else {
+          if code && (matches!(c, '"' | '\'') || (element == Element::Script && c == '`')) {
+            string = Some(c);
+          }
+          len
+        };
+
+        return (
+          State::RawText {
+            comment,
+            element,
+            string,
+          },
+          len,
+        );
+      }
+    };
+
+    (state, len)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[track_caller]
+  fn case(text: &str, expected: Result<Context, &str>) {
+    let mut html = Html::default();
+    html.text(text);
+    assert_eq!(html.interpolation(), expected.map_err(str::to_owned));
+  }
+
+  #[test]
+  fn text() {
+    case("", Ok(Context::Text));
+    case("<p>", Ok(Context::Text));
+    case("<p>foo</p>", Ok(Context::Text));
+    case("a < b", Ok(Context::Text));
+    case("<!-- <a href=\"", Ok(Context::Html));
+    case("<!-- foo --><p>", Ok(Context::Text));
+    case("<textarea><a href=\"", Ok(Context::Html));
+    case("<title>", Ok(Context::Html));
+  }
+
+  #[test]
+  fn attributes() {
+    case("<div class=\"", Ok(Context::Html));
+    case("<div class='foo ", Ok(Context::Html));
+    case("<div data-foo = \"", Ok(Context::Html));
+    case("<a href=\"", Ok(Context::Url));
+    case("<a HREF='", Ok(Context::Url));
+    case("<a href=\"/users/", Ok(Context::Html));
+    case("<img alt=\"\" src=\"", Ok(Context::Url));
+    case("<form action=\"", Ok(Context::Url));
+    case("<a href=\"foo\" title=\"", Ok(Context::Html));
+  }
+
+  #[test]
+  fn url_after_interpolation() {
+    let mut html = Html::default();
+    html.text("<a href=\"");
+    assert_eq!(html.interpolation(), Ok(Context::Url));
+    assert_eq!(html.interpolation(), Ok(Context::Html));
+    html.text("\">");
+    assert_eq!(html.interpolation(), Ok(Context::Text));
+  }
+
+  #[test]
+  fn tags() {
+    let error = Err("interpolations in HTML tags must be inside of quoted attribute values");
+    case("<", Ok(Context::Text));
+    case("<div", error);
+    case("<div ", error);
+    case("<div class", error);
+    case("<div class ", error);
+    case("<div class=\"foo\"", error);
+    case("</div", error);
+  }
+
+  #[test]
+  fn unsupported_attributes() {
+    let error = Err("interpolations in unquoted attribute values are not supported");
+    case("<div class=", error);
+    case("<div class=foo", error);
+    case(
+      "<button onclick=\"",
+      Err("interpolations in event handler attribute `onclick` are not supported"),
+    );
+    case(
+      "<div style=\"",
+      Err("interpolations in `style` attributes are not supported"),
+    );
+  }
+
+  #[test]
+  fn script() {
+    let error =
+      Err("interpolations in `<script>` elements must be inside of quoted string literals");
+    case("<script>", error);
+    case("<script>let x = \"", Ok(Context::Js));
+    case("<script type=\"module\">let x = '", Ok(Context::Js));
+    case("<script>let x = \"a\\\"", Ok(Context::Js));
+    case("<script>let x = \"a\";", error);
+    case("<script>let x = `", error);
+    case("<script>// \"\n", error);
+    case("<script>/* ' */ '", Ok(Context::Js));
+    case("<script>'</script>", Ok(Context::Text));
+    case("<script></script><p>", Ok(Context::Text));
+    case("<SCRIPT>'</Script>", Ok(Context::Text));
+  }
+
+  #[test]
+  fn style() {
+    let error =
+      Err("interpolations in `<style>` elements must be inside of quoted string literals");
+    case("<style>", error);
+    case("<style>a::after { content: \"", Ok(Context::Css));
+    case("<style>/* \" */", error);
+    case("<style>a { content: 'foo' }", error);
+    case("<style></style>", Ok(Context::Text));
+  }
+}
@@ -1,55 +1,113 @@
 use super::*;
 
-pub(crate) struct Implementation<'src> {
+/// How an interpolation is escaped.
+#[derive(Clone)]
+struct Escaping {
+  /// path to the escaper
+  escaper: String,
+  /// whether values which implement `HtmlSafe` are not escaped
+  html_safe: bool,
+}
+
+pub(crate) struct Implementation {
   pub(crate) body: TokenStream,
-  pub(crate) text: Vec<&'src str>,
+  /// included and extended templates, as named in their directives, and
+  /// their paths
+  pub(crate) includes: Vec<(String, String)>,
+  pub(crate) interpolations: usize,
+  pub(crate) text: Vec<String>,
 }
 
-impl<'src> Implementation<'src> {
-  fn line(i: usize, tokens: &[Token<'src>], token: Token, escape: bool, function: bool) -> String {
+impl Implementation {
+  /// The default estimate of the length of an interpolation's output.
+  pub(crate) const INTERPOLATION_SIZE_HINT: usize = 16;
+
+  fn line(
+    i: usize,
+    tokens: &[Token],
+    token: Token,
+    escaping: Option<&Escaping>,
+    label: Option<&str>,
+    function: bool,
+  ) -> Result<String, syn::Error> {
     let indent = indent(i, tokens).unwrap_or("");
     let error_handler = if function { ".unwrap()" } else { "?" };
-    match token {
+    let code = token.code().unwrap_or_default();
+    Ok(match token {
       Token::Text { index, .. } => {
         format!("boilerplate_output.write_str(boilerplate_text[{index}].as_ref()){error_handler} ;")
       }
-      Token::Code { contents } | Token::CodeLine { contents, .. } => contents.into(),
-      Token::Interpolation { contents } => {
+      Token::Code { .. } | Token::CodeLine { .. } => label
+        .and_then(|label| labeled(code, label))
+        .unwrap_or_else(|| code.into()),
+      Token::Comment { .. } | Token::CommentLine { .. } | Token::Escape { .. } => String::new(),
+      Token::Interpolation { .. } => {
         let trim = has_trailing_newline(i, tokens);
-        Self::interpolation(false, contents, error_handler, escape, indent, trim)
+        Self::interpolation(false, code, error_handler, escaping, indent, label, trim)?
       }
-      Token::InterpolationLine { contents, closed } => {
+      Token::InterpolationLine { closed, .. } => {
         let trim = closed || has_trailing_newline(i, tokens);
-        Self::interpolation(closed, contents, error_handler, escape, indent, trim)
+        Self::interpolation(closed, code, error_handler, escaping, indent, label, trim)?
       }
-    }
+    })
   }
 
   fn interpolation(
     append_newline: bool,
     contents: &str,
     error_handler: &str,
-    escape: bool,
+    escaping: Option<&Escaping>,
     indent: &str,
+    label: Option<&str>,
     trim: bool,
-  ) -> String {
+  ) -> Result<String, syn::Error> {
     use std::fmt::Write;
 
+    let (contents, trusted) = Filters::lower(contents)?;
+
     let mut output = String::new();
 
-    if escape {
+    // wrap the expression in a macro named after its location, so that the
+    // compiler names the location in errors in the expression, unless the
+    // expression contains `$`, which is special in macro definitions
+    let (definition, contents) = match label {
+      Some(label) if !contents.contains('$') => (
+        Some(format!(
+          "macro_rules! {label} {{ () => {{ {contents}\n }} }}"
+        )),
+        format!("{label}!()"),
+      ),
+      _ => (None, contents),
+    };
+
+    if let Some(Escaping {
+      escaper,
+      html_safe: true,
+    }) = escaping.filter(|_| !trusted)
+    {
+      // dispatch on whether the value implements `HtmlSafe`, using autoref
+      // specialization, so that `HtmlSafe` values are not escaped
       write!(
         output,
-        " ::boilerplate::Format::format(&({contents}), boilerplate_output, \"{indent}\", {trim})"
+        " ({{ use ::boilerplate::{{Interpolate as _, InterpolateHtmlSafe as _}}; \
+        (& ::boilerplate::Interpolation(&({contents}\n))).interpolate(boilerplate_output, \
+        & {escaper}, \"{indent}\", {trim}) }})"
+      )
+      .unwrap();
+    } else if let Some(Escaping { escaper, .. }) = escaping.filter(|_| !trusted) {
+      write!(
+        output,
+        " ::boilerplate::Format::format(&({contents}\n), boilerplate_output, & {escaper}, \
+        \"{indent}\", {trim})"
       )
       .unwrap();
     } else if indent.is_empty() && !trim {
-      write!(output, "write!(boilerplate_output, \"{{}}\", {contents})").unwrap();
+      write!(output, "write!(boilerplate_output, \"{{}}\", {contents}\n)").unwrap();
     } else {
       write!(
         output,
-        "write!(::boilerplate::Formatter::new(boilerplate_output, false, \"{indent}\", {trim}), \
-        \"{{}}\", {contents})"
+        "write!(::boilerplate::Formatter::new(boilerplate_output)\
+        .indent(\"{indent}\").trim({trim}), \"{{}}\", {contents}\n)"
       )
       .unwrap();
     }
@@ -64,27 +122,389 @@ impl<'src> Implementation<'src> {
       .unwrap();
     }
 
-    output
+    if let Some(definition) = definition {
+      output = format!("{{ {definition} {output} }}");
+    }
+
+    Ok(output)
   }
 
-  pub(crate) fn parse(src: &'src str, escape: bool, function: bool) -> Self {
-    let tokens = match Token::parse(src) {
-      Ok(tokens) => tokens,
-      Err(err) => panic!("{err}"),
-    };
+  pub(crate) fn parse(
+    src: &str,
+    source: &Source,
+    delimiters: &boilerplate_parser::Delimiters,
+    escape: Option<&Escape>,
+    function: bool,
+  ) -> Result<Self, syn::Error> {
+    Self::validate(src, source, delimiters)?;
+
+    let mut error = None;
+    let mut includes = Vec::new();
+
+    let expansion = boilerplate_parser::expand(src, delimiters, &mut |name| {
+      let result = templates(source.span())
+        .and_then(|templates| Source::from_path(&templates.join(name), source.span()))
+        .and_then(|include| {
+          let src = include.src()?;
+          Self::validate(&src, &include, delimiters)?;
+          if let Source::Path { path, .. } = include
+            && !includes.iter().any(|(included, _)| included == name)
+          {
+            includes.push((name.into(), path));
+          }
+          Ok(src)
+        });
+
+      result.map_err(|err| error = Some(err)).ok()
+    })
+    .map_err(|err| match (error, err) {
+      (Some(error), _) => error,
+      (None, boilerplate_parser::Error::Nested { error, path }) => templates(source.span())
+        .and_then(|templates| Source::from_path(&templates.join(path), source.span()))
+        .map_or_else(|err| err, |nested| nested.error(None, error)),
+      (None, err) => source.error(None, err),
+    })?;
+
+    let tokens = expansion.tokens();
+
+    // origins of code blocks and interpolations, which are kept by coalescing
+    let mut origins = tokens
+      .iter()
+      .zip(expansion.origins())
+      .filter(|(token, _origin)| token.code().is_some())
+      .map(|(_token, origin)| origin);
+
+    let text = Token::text_blocks(&tokens);
+
+    let tokens = Token::coalesce(&tokens)
+      .into_iter()
+      .map(|token| match token {
+        Token::Text { index, .. } => Token::Text {
+          contents: &text[index],
+          index,
+        },
+        _ => token,
+      })
+      .collect::<Vec<Token>>();
 
-    let text = tokens.iter().filter_map(|token| token.text()).collect();
+    // where each code block and interpolation came from
+    let origins = tokens
+      .iter()
+      .map(|token| token.code().and_then(|_| origins.next()))
+      .collect::<Vec<Option<Origin>>>();
 
-    let body = tokens
+    let interpolations = tokens
       .iter()
-      .enumerate()
-      .map(|(i, token)| Self::line(i, &tokens, *token, escape, function))
-      .collect::<String>()
-      .parse()
-      .unwrap();
+      .filter(|token| {
+        matches!(
+          token,
+          Token::Interpolation { .. } | Token::InterpolationLine { .. }
+        )
+      })
+      .count();
+
+    let escapers = Self::escapers(source, &includes, &tokens, &origins, escape)?;
+
+    let mut lines = Vec::<String>::new();
+
+    for (i, token) in tokens.iter().enumerate() {
+      // code blocks are only labeled at the start of a statement, where the
+      // label's macro definition can be inserted
+      let statement = lines
+        .iter()
+        .rev()
+        .find_map(|line| line.trim_end().chars().last())
+        .is_none_or(|last| matches!(last, ';' | '{' | '}'));
+
+      let label = origins[i]
+        .filter(|_| statement || !matches!(token, Token::Code { .. } | Token::CodeLine { .. }))
+        .and_then(|origin| {
+          let path = match (origin.path, source) {
+            (Some(name), _) => includes
+              .iter()
+              .find(|(included, _)| included == name)
+              .map(|(_, path)| path.as_str()),
+            (None, Source::Path { path, .. }) => Some(path.as_str()),
+            (None, Source::Literal(_)) => None,
+          };
+
+          path.map(|path| label(path, origin.span))
+        });
+
+      lines.push(
+        Self::line(
+          i,
+          &tokens,
+          *token,
+          escapers[i].as_ref(),
+          label.as_deref(),
+          function,
+        )
+        .map_err(|err| source.error(origins[i].map(|origin| origin.span), err))?,
+      );
+    }
+
+    let body = lines
+      .join("\n")
+      .parse::<TokenStream>()
+      .map_err(|err| source.error(None, err))?;
+
+    Ok(Self {
+      body: source.respan(body),
+      includes,
+      interpolations,
+      text,
+    })
+  }
+
+  /// Get the escaping of each token, which is `None` for tokens which are not
+  /// interpolations, or if the template is not escaped. Errors are located
+  /// in the template, `source` or one of `includes`, which `origins` says
+  /// each token came from.
+  fn escapers(
+    source: &Source,
+    includes: &[(String, String)],
+    tokens: &[Token],
+    origins: &[Option<Origin>],
+    escape: Option<&Escape>,
+  ) -> Result<Vec<Option<Escaping>>, syn::Error> {
+    let Some(escape) = escape else {
+      return Ok(vec![None; tokens.len()]);
+    };
+
+    let mut html = Html::default();
+
+    let mut escapers = Vec::new();
+
+    for (token, origin) in tokens.iter().zip(origins) {
+      let escaping = match token {
+        Token::Text { contents, .. } => {
+          html.text(contents);
+          None
+        }
+        Token::Interpolation { .. } | Token::InterpolationLine { .. } => {
+          let escaping = match escape {
+            // values which override the template's escaper may be used in
+            // contexts which cannot be escaped
+            Escape::Html if token.code().is_some_and(Self::overrides_escaper) => {
+              let context = html.interpolation().unwrap_or(Context::Html);
+              Escaping {
+                escaper: context.escaper().into(),
+                html_safe: context == Context::Text,
+              }
+            }
+            Escape::Html => {
+              let context = html.interpolation().map_err(|message| {
+                let include = origin
+                  .and_then(|origin| origin.path)
+                  .and_then(|name| includes.iter().find(|(included, _)| included == name));
+
+                if let Some((_, path)) = include {
+                  Source::Path {
+                    path: path.clone(),
+                    span: source.span(),
+                  }
+                  .error(origin.map(|origin| origin.span), message)
+                } else {
+                  source.error(origin.map(|origin| origin.span), message)
+                }
+              })?;
+              Escaping {
+                escaper: context.escaper().into(),
+                html_safe: context == Context::Text,
+              }
+            }
+            Escape::Escaper(path) => Escaping {
+              escaper: path.to_token_stream().to_string(),
+              html_safe: false,
+            },
+          };
+
+          if let Token::InterpolationLine { closed: true, .. } = token {
+            html.text("\n");
+          }
+
+          Some(escaping)
+        }
+        _ => None,
+      };
+
+      escapers.push(escaping);
+    }
+
+    Ok(escapers)
+  }
+
+  /// Whether the value of interpolation `code` is trusted with the `trusted`
+  /// filter, or wrapped in `Trusted` or `Escaped`, which override the
+  /// template's escaper.
+  fn overrides_escaper(code: &str) -> bool {
+    let Ok((code, trusted)) = Filters::lower(code) else {
+      return false;
+    };
+
+    trusted
+      || syn::parse_str::<Expr>(&code).is_ok_and(|expr| {
+        let Expr::Call(call) = expr else {
+          return false;
+        };
+
+        let Expr::Path(path) = &*call.func else {
+          return false;
+        };
+
+        path
+          .path
+          .segments
+          .last()
+          .is_some_and(|segment| segment.ident == "Trusted" || segment.ident == "Escaped")
+      })
+  }
+
+  /// An estimate of the length of the template's output, the sum of the
+  /// lengths of its text blocks plus `interpolation` for each interpolation.
+  pub(crate) fn size_hint(&self, interpolation: usize) -> usize {
+    self.text.iter().map(String::len).sum::<usize>() + self.interpolations * interpolation
+  }
+
+  /// Check `src` for parse errors and invalid interpolations, so that they
+  /// are reported with their location in `source`, rather than in the
+  /// template into which `src` is included.
+  fn validate(
+    src: &str,
+    source: &Source,
+    delimiters: &boilerplate_parser::Delimiters,
+  ) -> Result<(), syn::Error> {
+    for (token, span) in
+      Token::parse_spanned(src, delimiters).map_err(|err| source.error(None, err))?
+    {
+      if let Token::Interpolation { .. } | Token::InterpolationLine { .. } = token
+        && let Err(err) = Filters::parse(token.code().unwrap())
+      {
+        return Err(source.error(Some(span), err));
+      }
+    }
+
+    Ok(())
+  }
+}
+
+/// The name of the macro which wraps an interpolation at `span` in the
+/// template at `path`, for example `templates_index_html_42`, made from the
+/// template's path relative to the crate root and the interpolation's line.
+fn label(path: &str, span: boilerplate_parser::Span) -> String {
+  let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+
+  let path = Path::new(path)
+    .strip_prefix(manifest_dir)
+    .unwrap_or(Path::new(path));
+
+  let mut label = path
+    .to_string_lossy()
+    .chars()
+    .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+    .collect::<String>();
+
+  if label.starts_with(|c: char| c.is_ascii_digit()) {
+    label.insert(0, '_');
+  }
+
+  format!("{label}_{}", span.line + 1)
+}
+
+/// `code`, which starts a statement, with the expression at its head wrapped
+/// in a macro named `label`, so that the compiler names the macro in errors in
+/// the expression. Returns `None` if `code` has no such expression, like
+/// `} else {`, or if the expression contains `$`, which is special in macro
+/// definitions.
+fn labeled(code: &str, label: &str) -> Option<String> {
+  fn contains_let(expr: &Expr) -> bool {
+    match expr {
+      Expr::Let(_) => true,
+      Expr::Binary(binary) => contains_let(&binary.left) || contains_let(&binary.right),
+      Expr::Paren(paren) => contains_let(&paren.expr),
+      _ => false,
+    }
+  }
+
+  if code.contains('$') {
+    return None;
+  }
+
+  let (close, code) = match code.strip_prefix('}') {
+    Some(rest) => ("} ", rest.trim_start()),
+    None => ("", code),
+  };
+
+  // the macro cannot be defined before `else if`, so it is defined in a block
+  // in the condition
+  if let Some(rest) = code
+    .strip_prefix("else")
+    .filter(|rest| rest.starts_with(char::is_whitespace))
+  {
+    let Expr::If(ExprIf { cond, .. }) =
+      syn::parse_str::<Expr>(&format!("{} {{}}", rest.strip_suffix('{')?)).ok()?
+    else {
+      return None;
+    };
 
-    Self { body, text }
+    if contains_let(&cond) {
+      return None;
+    }
+
+    return Some(format!(
+      "{close}else if {{ macro_rules! {label} {{ () => {{ {} }} }} {label}!() }} {{",
+      cond.to_token_stream(),
+    ));
   }
+
+  let (head, expr) = if let Some(head) = code.strip_suffix('{') {
+    match syn::parse_str::<Expr>(&format!("{head} {{}}")).ok()? {
+      Expr::If(ExprIf { cond, .. }) => match *cond {
+        Expr::Let(ExprLet { expr, pat, .. }) => (quote!(if let #pat =), expr),
+        cond if !contains_let(&cond) => (quote!(if), Box::new(cond)),
+        _ => return None,
+      },
+      Expr::While(ExprWhile {
+        cond, label: None, ..
+      }) => match *cond {
+        Expr::Let(ExprLet { expr, pat, .. }) => (quote!(while let #pat =), expr),
+        cond if !contains_let(&cond) => (quote!(while), Box::new(cond)),
+        _ => return None,
+      },
+      Expr::ForLoop(ExprForLoop {
+        expr,
+        label: None,
+        pat,
+        ..
+      }) => (quote!(for #pat in), expr),
+      Expr::Match(ExprMatch { expr, .. }) => (quote!(match), expr),
+      _ => return None,
+    }
+  } else if code.ends_with(';') {
+    match syn::parse_str::<Stmt>(code).ok()? {
+      Stmt::Local(Local {
+        init: Some(LocalInit {
+          expr,
+          diverge: None,
+          ..
+        }),
+        pat,
+        ..
+      }) => (quote!(let #pat =), expr),
+      Stmt::Expr(expr, Some(_)) => (quote!(), Box::new(expr)),
+      _ => return None,
+    }
+  } else {
+    return None;
+  };
+
+  let tail = if code.ends_with('{') { "{" } else { ";" };
+
+  Some(format!(
+    "{close}macro_rules! {label} {{ () => {{ {} }} }} {head} {label}!() {tail}",
+    expr.to_token_stream(),
+  ))
 }
 
 fn has_trailing_newline(i: usize, tokens: &[Token]) -> bool {
@@ -131,7 +551,7 @@ mod tests {
 
   #[track_caller]
   fn indentation(src: &str, expected: &[&str]) {
-    let tokens = Token::parse(src).unwrap();
+    let tokens = Token::parse(src, &boilerplate_parser::Delimiters::DEFAULT).unwrap();
     let actual = tokens
       .iter()
       .enumerate()
@@ -149,7 +569,7 @@ mod tests {
   fn trailing_newline() {
     #[track_caller]
     fn case(src: &str, expected: &[bool]) {
-      let tokens = Token::parse(src).unwrap();
+      let tokens = Token::parse(src, &boilerplate_parser::Delimiters::DEFAULT).unwrap();
       let actual = tokens
         .iter()
         .enumerate()
@@ -232,4 +652,221 @@ mod tests {
   fn empty_template() {
     indentation("", &[]);
   }
+
+  #[test]
+  fn label_names() {
+    let span = boilerplate_parser::Span {
+      column: 0,
+      end: 0,
+      line: 41,
+      start: 0,
+    };
+
+    assert_eq!(
+      label(
+        &format!("{}/templates/foo-bar.html", env!("CARGO_MANIFEST_DIR")),
+        span,
+      ),
+      "templates_foo_bar_html_42",
+    );
+
+    assert_eq!(label("0.html", span), "_0_html_42");
+  }
+
+  #[test]
+  fn labels() {
+    #[track_caller]
+    fn case(code: &str, expected: Option<&str>) {
+      assert_eq!(labeled(code, "foo_html_1").as_deref(), expected);
+    }
+
+    case(
+      "if x == 1 {",
+      Some("macro_rules! foo_html_1 { () => { x == 1 } } if foo_html_1!() {"),
+    );
+    case(
+      "} else if x {",
+      Some("} else if { macro_rules! foo_html_1 { () => { x } } foo_html_1!() } {"),
+    );
+    case(
+      "if let Some(y) = x {",
+      Some("macro_rules! foo_html_1 { () => { x } } if let Some (y) = foo_html_1!() {"),
+    );
+    case(
+      "while x {",
+      Some("macro_rules! foo_html_1 { () => { x } } while foo_html_1!() {"),
+    );
+    case(
+      "for (a, b) in x.iter() {",
+      Some("macro_rules! foo_html_1 { () => { x . iter () } } for (a , b) in foo_html_1!() {"),
+    );
+    case(
+      "match x {",
+      Some("macro_rules! foo_html_1 { () => { x } } match foo_html_1!() {"),
+    );
+    case(
+      "let y: u8 = x;",
+      Some("macro_rules! foo_html_1 { () => { x } } let y : u8 = foo_html_1!() ;"),
+    );
+    case(
+      "x.push(1);",
+      Some("macro_rules! foo_html_1 { () => { x . push (1) } }  foo_html_1!() ;"),
+    );
+    case("} else {", None);
+    case("}", None);
+    case("if let Some(y) = x && y {", None);
+    case("let Some(y) = x else { return };", None);
+    case("'a: for y in x {", None);
+    case("use foo::bar;", None);
+    case("x.push(\"$\");", None);
+  }
+
+  #[test]
+  fn parse_errors() {
+    assert_eq!(
+      Implementation::parse(
+        "foo\n  {{ bar",
+        &Source::Path {
+          path: "templates/foo.html".into(),
+          span: Span::call_site(),
+        },
+        &boilerplate_parser::Delimiters::DEFAULT,
+        None,
+        false,
+      )
+      .err()
+      .unwrap()
+      .to_string(),
+      "templates/foo.html: unmatched `{{` at line 2, column 3",
+    );
+  }
+
+  #[test]
+  fn include_errors() {
+    let err = Implementation::parse(
+      "{% include \"missing.html\" %}",
+      &Source::Literal(LitStr::new("", Span::call_site())),
+      &boilerplate_parser::Delimiters::DEFAULT,
+      None,
+      false,
+    )
+    .err()
+    .unwrap()
+    .to_string();
+
+    assert!(
+      err.starts_with("failed to read template `") && err.contains("missing.html"),
+      "{err}",
+    );
+  }
+
+  #[test]
+  fn errors_are_located_in_includes() {
+    let err = Implementation::parse(
+      "{% include \"unclosed-block.txt\" %}",
+      &Source::Literal(LitStr::new("", Span::call_site())),
+      &boilerplate_parser::Delimiters::DEFAULT,
+      None,
+      false,
+    )
+    .err()
+    .unwrap()
+    .to_string();
+
+    assert!(
+      err.ends_with("templates/unclosed-block.txt: unclosed block `q` at line 2, column 1"),
+      "{err}",
+    );
+  }
+
+  #[test]
+  fn context_errors_are_located_in_includes() {
+    let err = Implementation::parse(
+      "<p>\n{% include \"context-error.html\" %}",
+      &Source::Literal(LitStr::new("", Span::call_site())),
+      &boilerplate_parser::Delimiters::DEFAULT,
+      Some(&Escape::Html),
+      false,
+    )
+    .err()
+    .unwrap()
+    .to_string();
+
+    assert!(
+      err.ends_with(
+        "templates/context-error.html:2:3: \
+        interpolations in HTML tags must be inside of quoted attribute values"
+      ),
+      "{err}",
+    );
+  }
+
+  #[test]
+  fn overriding_escaper_skips_context_errors() {
+    #[track_caller]
+    fn case(src: &str, ok: bool) {
+      let result = Implementation::parse(
+        src,
+        &Source::Literal(LitStr::new("", Span::call_site())),
+        &boilerplate_parser::Delimiters::DEFAULT,
+        Some(&Escape::Html),
+        false,
+      );
+
+      assert_eq!(result.is_ok(), ok, "{src}");
+    }
+
+    case("<input {{ self.attrs }}>", false);
+    case("<input {{ boilerplate::Trusted(self.attrs) }}>", true);
+    case("<input {{ self.attrs |> trusted }}>", true);
+    case("<a href={{ Trusted(x) }}>", true);
+    case("<a onclick=\"{{ x |> trusted }}\">", true);
+    case("<p style=\"{{ Trusted(x) }}\">", true);
+    case("<script>{{ Escaped(Json, x) }}</script>", true);
+    case("<style>{{ x |> trusted }}</style>", true);
+    case("<style>{{ x |> upper }}</style>", false);
+    case("<input {{ Untrusted(x) }}>", false);
+  }
+
+  #[test]
+  fn interpolation_errors_are_located() {
+    #[track_caller]
+    fn case(src: &str, source: Source, expected: &str) {
+      assert_eq!(
+        Implementation::parse(
+          src,
+          &source,
+          &boilerplate_parser::Delimiters::DEFAULT,
+          None,
+          false,
+        )
+        .err()
+        .unwrap()
+        .to_string(),
+        expected,
+      );
+    }
+
+    case(
+      "foo\n  {{ 1 + }}",
+      Source::Path {
+        path: "templates/foo.html".into(),
+        span: Span::call_site(),
+      },
+      "templates/foo.html:2:3: unexpected end of input, expected an expression",
+    );
+    case(
+      "foo\n$$ 1 2\n",
+      Source::Path {
+        path: "templates/foo.html".into(),
+        span: Span::call_site(),
+      },
+      "templates/foo.html:2:1: unexpected token",
+    );
+    case(
+      "{{ x }}{{ }}",
+      Source::Literal(LitStr::new("", Span::call_site())),
+      "line 1, column 8: unexpected end of input, expected an expression",
+    );
+  }
 }
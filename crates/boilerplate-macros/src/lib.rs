@@ -1,27 +1,122 @@
 use {
   self::{
-    boilerplate::Boilerplate, implementation::Implementation, source::Source, template::Template,
+    boilerplate::Boilerplate,
+    delimiters::Delimiters,
+    escape::Escape,
+    filters::Filters,
+    function::Function,
+    html::{Context, Html},
+    implementation::Implementation,
+    source::Source,
+    template::Template,
+    templates::{manifest_dir, templates, track_templates},
+    variant::Variant,
+  },
+  boilerplate_parser::{Origin, Token},
+  darling::{
+    FromDeriveInput, FromMeta, FromVariant,
+    ast::{Data, Fields, Style},
   },
-  boilerplate_parser::Token,
-  darling::FromDeriveInput,
   new_mime_guess::Mime,
-  proc_macro2::{Span, TokenStream},
+  proc_macro2::{Group, Spacing, Span, TokenStream, TokenTree},
   quote::{ToTokens, TokenStreamExt, quote},
-  std::path::Path,
-  syn::{DeriveInput, Generics, Ident, LitStr, parse_macro_input},
+  serde::Deserialize,
+  std::{
+    fmt::Display,
+    io, mem,
+    path::{Path, PathBuf},
+  },
+  syn::{
+    DeriveInput, Expr, ExprForLoop, ExprIf, ExprLet, ExprMatch, ExprWhile, GenericParam, Generics,
+    Ident, LitStr, Local, LocalInit, Stmt,
+    parse::{Parse, ParseStream},
+    parse_macro_input,
+  },
 };
 
 mod boilerplate;
+mod delimiters;
+mod escape;
+mod filters;
+mod function;
+mod html;
 mod implementation;
 mod source;
 mod template;
+mod templates;
+mod variant;
 
 #[proc_macro]
 pub fn boilerplate(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
-  let template = parse_macro_input!(input as LitStr);
-  let src = template.value();
+  function(input, false)
+}
+
+#[proc_macro]
+pub fn boilerplate_display(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+  function(input, true)
+}
+
+/// Expand `boilerplate!`, which renders the template to a `String`, or
+/// `boilerplate_display!`, which returns a value which renders the template
+/// when displayed.
+fn function(input: proc_macro::TokenStream, display: bool) -> proc_macro::TokenStream {
+  let Function { escape, source } = parse_macro_input!(input as Function);
+
+  let implementation = match source.src().and_then(|src| {
+    Implementation::parse(
+      &src,
+      &source,
+      &boilerplate_parser::Delimiters::DEFAULT,
+      escape.as_ref(),
+      !display,
+    )
+  }) {
+    Ok(implementation) => implementation,
+    Err(err) => return err.to_compile_error().into(),
+  };
+
+  let size_hint = implementation.size_hint(Implementation::INTERPOLATION_SIZE_HINT);
+
+  let Implementation {
+    body,
+    includes,
+    text,
+    ..
+  } = implementation;
+
+  let includes = includes.iter().map(|(_name, path)| path);
+
+  // include template files, so changes trigger a rebuild
+  let template = if let Source::Path { .. } = source {
+    Some(quote!(const _: &str = #source;))
+  } else {
+    None
+  };
+
+  let track_templates = track_templates();
 
-  let Implementation { body, text } = Implementation::parse(&src, false, true);
+  if display {
+    let html_safe = matches!(escape, Some(Escape::Html));
+
+    return quote! {
+      {
+        #template
+
+        #(const _: &str = include_str!(#includes);)*
+
+        #track_templates
+
+        ::boilerplate::DisplayFn::<_, #html_safe>::new(move |boilerplate_output| {
+          use ::core::fmt::Write;
+          let boilerplate_output: &mut dyn ::core::fmt::Write = boilerplate_output;
+          let boilerplate_text = &[ #(#text),* ];
+          #body
+          Ok(())
+        })
+      }
+    }
+    .into();
+  }
 
   quote! {
     {
@@ -29,8 +124,14 @@ pub fn boilerplate(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
 
       use ::core::fmt::Write;
 
+      #template
+
+      #(const _: &str = include_str!(#includes);)*
+
+      #track_templates
+
       let boilerplate_text = &[ #(#text),* ];
-      let mut boilerplate_output = alloc::string::String::new();
+      let mut boilerplate_output = alloc::string::String::with_capacity(#size_hint);
 
       {
         let boilerplate_output = &mut boilerplate_output;
@@ -48,8 +149,11 @@ pub fn boilerplate(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
 pub fn Boilerplate(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
   let derive_input = parse_macro_input!(item as DeriveInput);
 
-  Boilerplate::from_derive_input(&derive_input)
-    .expect("Failed to parse derive input")
-    .impls()
-    .into()
+  match Boilerplate::from_derive_input(&derive_input) {
+    Ok(boilerplate) => boilerplate
+      .impls()
+      .unwrap_or_else(syn::Error::into_compile_error),
+    Err(err) => err.write_errors(),
+  }
+  .into()
 }
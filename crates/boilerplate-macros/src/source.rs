@@ -1,16 +1,84 @@
 use super::*;
 
 pub(crate) enum Source {
-  Path(String),
+  Path { path: String, span: Span },
   Literal(LitStr),
 }
 
 impl Source {
-  pub(crate) fn src(&self) -> String {
+  pub(crate) fn from_path(path: &Path, span: Span) -> Result<Self, syn::Error> {
+    let Some(path) = path.to_str() else {
+      return Err(syn::Error::new(
+        span,
+        format!("path to template `{}` is not valid unicode", path.display()),
+      ));
+    };
+
+    Ok(Self::Path {
+      path: path.into(),
+      span,
+    })
+  }
+
+  /// Create an error for `message`, located at `location` in the template, if
+  /// known. Errors in file templates are prefixed with the template path, and
+  /// point to the `filename` attribute or the context type, while errors in
+  /// literal templates point to the literal.
+  pub(crate) fn error(
+    &self,
+    location: Option<boilerplate_parser::Span>,
+    message: impl Display,
+  ) -> syn::Error {
+    match (self, location) {
+      (Self::Literal(literal), Some(location)) => syn::Error::new(
+        literal.span(),
+        format!(
+          "line {}, column {}: {message}",
+          location.line + 1,
+          location.column + 1,
+        ),
+      ),
+      (Self::Literal(literal), None) => syn::Error::new(literal.span(), message),
+      (Self::Path { path, span }, Some(location)) => {
+        syn::Error::new(*span, format!("{path}:{location}: {message}"))
+      }
+      (Self::Path { path, span }, None) => syn::Error::new(*span, format!("{path}: {message}")),
+    }
+  }
+
+  /// Point the tokens of generated code at the template literal, or for
+  /// templates read from files, at the `filename` or `path` attribute, so that
+  /// the compiler reports errors in template code there, instead of at the
+  /// macro invocation.
+  pub(crate) fn respan(&self, stream: TokenStream) -> TokenStream {
+    fn respan(stream: TokenStream, span: Span) -> TokenStream {
+      stream
+        .into_iter()
+        .map(|mut tree| {
+          if let TokenTree::Group(group) = &mut tree {
+            *group = Group::new(group.delimiter(), respan(group.stream(), span));
+          }
+          tree.set_span(span);
+          tree
+        })
+        .collect()
+    }
+
+    respan(stream, Span::call_site().located_at(self.span()))
+  }
+
+  pub(crate) fn span(&self) -> Span {
+    match self {
+      Self::Literal(literal) => literal.span(),
+      Self::Path { span, .. } => *span,
+    }
+  }
+
+  pub(crate) fn src(&self) -> Result<String, syn::Error> {
     match self {
-      Self::Literal(literal) => literal.value(),
-      Self::Path(path) => std::fs::read_to_string(path)
-        .unwrap_or_else(|err| panic!("Failed to read template `{path}`: {err}")),
+      Self::Literal(literal) => Ok(literal.value()),
+      Self::Path { path, span } => std::fs::read_to_string(path)
+        .map_err(|err| syn::Error::new(*span, format!("failed to read template `{path}`: {err}"))),
     }
   }
 }
@@ -19,7 +87,7 @@ impl ToTokens for Source {
   fn to_tokens(&self, tokens: &mut TokenStream) {
     match self {
       Self::Literal(literal) => tokens.append(literal.token()),
-      Self::Path(path) => {
+      Self::Path { path, .. } => {
         let path = LitStr::new(path, Span::call_site());
         tokens.append_all(quote!(include_str!(#path)));
       }
@@ -2,16 +2,19 @@ use super::*;
 
 pub(crate) struct Template {
   pub(crate) axum: Option<bool>,
-  pub(crate) escape: bool,
+  pub(crate) delimiters: Delimiters,
+  pub(crate) error: Option<syn::Path>,
+  pub(crate) escape: Option<Escape>,
   pub(crate) generics: Generics,
   pub(crate) ident: Ident,
+  pub(crate) interpolation_size_hint: usize,
   pub(crate) mime: Mime,
   pub(crate) source: Source,
 }
 
 impl Template {
-  pub(crate) fn impls(self) -> TokenStream {
-    let display_impl = self.display_impl();
+  pub(crate) fn impls(&self, implementation: Implementation) -> Result<TokenStream, syn::Error> {
+    let display_impl = self.display_impl(implementation)?;
 
     let axum_into_response_impl = if self.axum.unwrap_or(cfg!(feature = "axum")) {
       Some(self.axum_into_response_impl())
@@ -19,23 +22,116 @@ impl Template {
       None
     };
 
-    quote! {
+    let html_safe_impl = if let Some(Escape::Html) = self.escape {
+      Some(self.html_safe_impl())
+    } else {
+      None
+    };
+
+    let track_templates = track_templates();
+
+    Ok(quote! {
       #display_impl
+      #html_safe_impl
       #axum_into_response_impl
-    }
+      #track_templates
+    })
   }
 
-  fn display_impl(&self) -> TokenStream {
+  /// Parse the template.
+  pub(crate) fn implementation(&self) -> Result<Implementation, syn::Error> {
+    Implementation::parse(
+      &self.source.src()?,
+      &self.source,
+      &self.delimiters.get(),
+      self.escape.as_ref(),
+      false,
+    )
+  }
+
+  fn display_impl(&self, implementation: Implementation) -> Result<TokenStream, syn::Error> {
     let ident = &self.ident;
     let source = &self.source;
-    let src = source.src();
 
-    let Implementation { body, text } = Implementation::parse(&src, self.escape, false);
+    let size_hint = implementation.size_hint(self.interpolation_size_hint);
+
+    let Implementation {
+      body,
+      includes,
+      text,
+      ..
+    } = implementation;
+
+    let paths = includes.iter().map(|(_name, path)| path);
+
+    // include included templates, so changes trigger a rebuild
+    let template = if includes.is_empty() {
+      quote!(#source)
+    } else {
+      quote!({
+        #(const _: &str = include_str!(#paths);)*
+        #source
+      })
+    };
 
     let (impl_generics, ty_generics, where_clause) = self.generics.split_for_impl();
 
+    // with `#[boilerplate(error = ...)]`, the template is rendered by an
+    // inherent method which returns the error type, so template code can use
+    // `?`, and the trait method discards the error
+    let (body, fallible_impl) = if let Some(error) = &self.error {
+      (
+        quote! {
+          Self::boilerplate_fallible(self, boilerplate_text, boilerplate_output)
+            .map_err(|_| ::core::fmt::Error)
+        },
+        Some(quote! {
+          const _: () = {
+            extern crate alloc;
+
+            impl #impl_generics #ident #ty_generics #where_clause {
+              fn boilerplate_fallible(
+                &self,
+                boilerplate_text: &[impl ::core::convert::AsRef<str>],
+                boilerplate_output: &mut dyn ::core::fmt::Write,
+              ) -> ::core::result::Result<(), #error> {
+                use ::core::fmt::Write;
+                use ::boilerplate::Format;
+                #body
+                Ok(())
+              }
+
+              /// Render the template to a new `String`, returning errors from
+              /// template code.
+              pub fn try_render(&self) -> ::core::result::Result<alloc::string::String, #error> {
+                let mut output = alloc::string::String::with_capacity(
+                  <Self as ::boilerplate::Boilerplate>::SIZE_HINT,
+                );
+                Self::boilerplate_fallible(
+                  self,
+                  <Self as ::boilerplate::Boilerplate>::TEXT,
+                  &mut output,
+                )?;
+                Ok(output)
+              }
+            }
+          };
+        }),
+      )
+    } else {
+      (
+        quote! {
+          use ::core::fmt::Write;
+          use ::boilerplate::Format;
+          #body
+          Ok(())
+        },
+        None,
+      )
+    };
+
     let path = if cfg!(feature = "reload") {
-      if let Source::Path(path) = &self.source {
+      if let Source::Path { path, .. } = &self.source {
         Some(quote!(const PATH: Option<&'static str> = Some(#path);))
       } else {
         Some(quote!(
@@ -46,26 +142,57 @@ impl Template {
       None
     };
 
-    quote! {
+    let delimiters = if cfg!(feature = "reload") {
+      let delimiters = &self.delimiters;
+      Some(quote!(const DELIMITERS: ::boilerplate::Delimiters<'static> = #delimiters;))
+    } else {
+      None
+    };
+
+    let root = if cfg!(feature = "reload") {
+      let root = templates(self.ident.span())?.to_string_lossy().into_owned();
+      Some(quote!(const ROOT: &'static str = #root;))
+    } else {
+      None
+    };
+
+    let includes = if cfg!(feature = "reload") {
+      let (names, paths) = includes.into_iter().unzip::<_, _, Vec<_>, Vec<_>>();
+      Some(quote!(
+        const INCLUDES: &'static [(&'static str, &'static str)] =
+          &[ #((#names, include_str!(#paths))),* ];
+      ))
+    } else {
+      None
+    };
+
+    Ok(quote! {
       impl #impl_generics ::boilerplate::Boilerplate for #ident #ty_generics #where_clause {
-        const TEMPLATE: &'static str = #source;
+        const TEMPLATE: &'static str = #template;
 
         const TEXT: &'static [&'static str] = &[ #(#text),* ];
 
+        const SIZE_HINT: usize = #size_hint;
+
+        #delimiters
+
+        #includes
+
         #path
 
+        #root
+
         fn boilerplate(
           &self,
           boilerplate_text: &[impl ::core::convert::AsRef<str>],
-          boilerplate_output: &mut ::core::fmt::Formatter,
+          boilerplate_output: &mut dyn ::core::fmt::Write,
         ) -> ::core::fmt::Result {
-          use ::core::fmt::Write;
-          use ::boilerplate::Format;
           #body
-          Ok(())
         }
       }
 
+      #fallible_impl
+
       impl #impl_generics ::core::fmt::Display for #ident #ty_generics #where_clause {
         fn fmt(&self, boilerplate_output: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
           <Self as ::boilerplate::Boilerplate>::boilerplate(
@@ -75,6 +202,15 @@ impl Template {
           )
         }
       }
+    })
+  }
+
+  fn html_safe_impl(&self) -> TokenStream {
+    let ident = &self.ident;
+    let (impl_generics, ty_generics, where_clause) = self.generics.split_for_impl();
+
+    quote! {
+      impl #impl_generics ::boilerplate::HtmlSafe for #ident #ty_generics #where_clause {}
     }
   }
 
@@ -83,14 +219,27 @@ impl Template {
     let content_type = LitStr::new(self.mime.as_ref(), Span::call_site());
     let (impl_generics, ty_generics, where_clause) = self.generics.split_for_impl();
 
+    // errors from template code are expected, and result in an internal
+    // server error response, whereas write errors are not
+    let body = if self.error.is_some() {
+      quote! {
+        match self.try_render() {
+          Ok(body) => body,
+          Err(_) => {
+            return ::axum::http::StatusCode::INTERNAL_SERVER_ERROR.into_response();
+          }
+        }
+      }
+    } else {
+      quote!(<Self as ::boilerplate::Boilerplate>::render(&self).unwrap())
+    };
+
     quote! {
       impl #impl_generics ::axum::response::IntoResponse for #ident #ty_generics #where_clause {
         fn into_response(self) -> ::axum::response::Response {
-          extern crate alloc;
-          use alloc::string::ToString;
           (
             [(::axum::http::header::CONTENT_TYPE, #content_type)],
-            self.to_string(),
+            #body,
           ).into_response()
         }
       }
@@ -118,35 +267,82 @@ mod tests {
       None
     };
 
+    let delimiters = if cfg!(feature = "reload") {
+      Some(quote!(
+        const DELIMITERS: ::boilerplate::Delimiters<'static> = ::boilerplate::Delimiters {
+          code: ("{%", "%}"),
+          code_line: "%%",
+          comment: ("{{#", "#}}"),
+          comment_line: "$$#",
+          interpolation: ("{{", "}}"),
+          interpolation_line: "$$",
+        };
+      ))
+    } else {
+      None
+    };
+
+    let includes = if cfg!(feature = "reload") {
+      Some(quote!(
+        const INCLUDES: &'static [(&'static str, &'static str)] = &[];
+      ))
+    } else {
+      None
+    };
+
+    let root = if cfg!(feature = "reload") {
+      let root = templates(Span::call_site())
+        .unwrap()
+        .to_string_lossy()
+        .into_owned();
+      Some(quote!(const ROOT: &'static str = #root;))
+    } else {
+      None
+    };
+
     let body = if cfg!(feature = "reload") {
       Some(quote!(boilerplate_output.write_str(boilerplate_text[0].as_ref())?;))
     } else {
       None
     };
 
+    let template = Template {
+      axum: None,
+      delimiters: Delimiters::default(),
+      error: None,
+      escape: None,
+      generics: Generics::default(),
+      ident: Ident::new("Foo", Span::call_site()),
+      interpolation_size_hint: 16,
+      mime: mime::TEXT_PLAIN,
+      source: Source::Literal(LitStr::new("", Span::call_site())),
+    };
+
     assert_eq!(
-      Template {
-        axum: None,
-        escape: false,
-        generics: Generics::default(),
-        ident: Ident::new("Foo", Span::call_site()),
-        mime: mime::TEXT_PLAIN,
-        source: Source::Literal(LitStr::new("", Span::call_site())),
-      }
-      .display_impl()
-      .to_string(),
+      template
+        .display_impl(template.implementation().unwrap())
+        .unwrap()
+        .to_string(),
       quote!(
         impl ::boilerplate::Boilerplate for Foo {
             const TEMPLATE: &'static str = "";
 
             const TEXT: &'static [&'static str] = &[#text];
 
+            const SIZE_HINT: usize = 0usize;
+
+            #delimiters
+
+            #includes
+
             #path
 
+            #root
+
             fn boilerplate(
               &self,
               boilerplate_text: &[impl ::core::convert::AsRef<str>],
-              boilerplate_output: &mut ::core::fmt::Formatter,
+              boilerplate_output: &mut dyn ::core::fmt::Write,
             ) -> ::core::fmt::Result {
               use ::core::fmt::Write;
               use ::boilerplate::Format;
@@ -169,20 +365,38 @@ mod tests {
     );
   }
 
+  fn literal(template: &str) -> Source {
+    Source::Literal(LitStr::new(template, Span::call_site()))
+  }
+
   fn assert_display_body_eq(template: &str, expected: TokenStream) {
     assert_eq!(
-      Implementation::parse(template, false, false)
-        .body
-        .to_string(),
+      Implementation::parse(
+        template,
+        &literal(template),
+        &boilerplate_parser::Delimiters::DEFAULT,
+        None,
+        false,
+      )
+      .unwrap()
+      .body
+      .to_string(),
       expected.to_string(),
     );
   }
 
   fn assert_escape_body_eq(template: &str, expected: TokenStream) {
     assert_eq!(
-      Implementation::parse(template, true, false)
-        .body
-        .to_string(),
+      Implementation::parse(
+        template,
+        &literal(template),
+        &boilerplate_parser::Delimiters::DEFAULT,
+        Some(&Escape::Html),
+        false,
+      )
+      .unwrap()
+      .body
+      .to_string(),
       expected.to_string(),
     );
   }
@@ -268,6 +482,57 @@ mod tests {
     }
   }
 
+  #[test]
+  fn size_hint() {
+    #[track_caller]
+    fn case(template: &str, interpolation: usize, expected: usize) {
+      assert_eq!(
+        Implementation::parse(
+          template,
+          &literal(template),
+          &boilerplate_parser::Delimiters::DEFAULT,
+          None,
+          false,
+        )
+        .unwrap()
+        .size_hint(interpolation),
+        expected,
+      );
+    }
+
+    case("", 16, 0);
+    case("foo", 16, 3);
+    case("foo {{ bar }} baz", 16, 24);
+    case("foo {{ bar }} baz\n$$ qux\n", 10, 29);
+    case("{% if true { %}foo{% } %}", 16, 3);
+  }
+
+  #[test]
+  fn line_comments() {
+    if cfg!(feature = "reload") {
+      assert_display_body_eq(
+        "foo\n%% let x = 1; // }}\n{{ x // }}\n}}bar",
+        quote!(
+          boilerplate_output.write_str(boilerplate_text[0].as_ref())?;
+          let x = 1;
+          boilerplate_output.write_str(boilerplate_text[1].as_ref())?;
+          write!(boilerplate_output, "{}", x)?;
+          boilerplate_output.write_str(boilerplate_text[2].as_ref())?;
+        ),
+      );
+    } else {
+      assert_display_body_eq(
+        "foo\n%% let x = 1; // }}\n{{ x // }}\n}}bar",
+        quote!(
+          boilerplate_output.write_str(boilerplate_text[0].as_ref())?;
+          let x = 1;
+          write!(boilerplate_output, "{}", x)?;
+          boilerplate_output.write_str(boilerplate_text[1].as_ref())?;
+        ),
+      );
+    }
+  }
+
   #[test]
   fn non_trailing_text() {
     if cfg!(feature = "reload") {
@@ -305,7 +570,15 @@ mod tests {
         "{{ true }}",
         quote!(
           boilerplate_output.write_str(boilerplate_text[0].as_ref())?;
-          ::boilerplate::Format::format(&(true), boilerplate_output, "", false)?;
+          ({
+            use ::boilerplate::{Interpolate as _, InterpolateHtmlSafe as _};
+            (&::boilerplate::Interpolation(&(true))).interpolate(
+              boilerplate_output,
+              &::boilerplate::escaper::Html,
+              "",
+              false
+            )
+          })?;
           boilerplate_output.write_str(boilerplate_text[1].as_ref())?;
         ),
       );
@@ -313,7 +586,15 @@ mod tests {
       assert_escape_body_eq(
         "{{ true }}",
         quote!(
-          ::boilerplate::Format::format(&(true), boilerplate_output, "", false)?;
+          ({
+            use ::boilerplate::{Interpolate as _, InterpolateHtmlSafe as _};
+            (&::boilerplate::Interpolation(&(true))).interpolate(
+              boilerplate_output,
+              &::boilerplate::escaper::Html,
+              "",
+              false
+            )
+          })?;
         ),
       );
     }
@@ -326,7 +607,15 @@ mod tests {
         "    {{ true }}",
         quote!(
           boilerplate_output.write_str(boilerplate_text[0].as_ref())?;
-          ::boilerplate::Format::format(&(true), boilerplate_output, "    ", false)?;
+          ({
+            use ::boilerplate::{Interpolate as _, InterpolateHtmlSafe as _};
+            (&::boilerplate::Interpolation(&(true))).interpolate(
+              boilerplate_output,
+              &::boilerplate::escaper::Html,
+              "    ",
+              false
+            )
+          })?;
           boilerplate_output.write_str(boilerplate_text[1].as_ref())?;
         ),
       );
@@ -335,7 +624,15 @@ mod tests {
         "    {{ true }}",
         quote!(
           boilerplate_output.write_str(boilerplate_text[0].as_ref())?;
-          ::boilerplate::Format::format(&(true), boilerplate_output, "    ", false)?;
+          ({
+            use ::boilerplate::{Interpolate as _, InterpolateHtmlSafe as _};
+            (&::boilerplate::Interpolation(&(true))).interpolate(
+              boilerplate_output,
+              &::boilerplate::escaper::Html,
+              "    ",
+              false
+            )
+          })?;
         ),
       );
     }
@@ -349,7 +646,7 @@ mod tests {
         quote!(
           boilerplate_output.write_str(boilerplate_text[0].as_ref())?;
           write!(
-            ::boilerplate::Formatter::new(boilerplate_output, false, "    ", false),
+            ::boilerplate::Formatter::new(boilerplate_output).indent("    ").trim(false),
             "{}",
             true
           )?;
@@ -362,7 +659,7 @@ mod tests {
         quote!(
           boilerplate_output.write_str(boilerplate_text[0].as_ref())?;
           write!(
-            ::boilerplate::Formatter::new(boilerplate_output, false, "    ", false),
+            ::boilerplate::Formatter::new(boilerplate_output).indent("    ").trim(false),
             "{}",
             true
           )?;
@@ -379,7 +676,7 @@ mod tests {
         quote!(
           boilerplate_output.write_str(boilerplate_text[0].as_ref())?;
           write!(
-            ::boilerplate::Formatter::new(boilerplate_output, false, "    ", true),
+            ::boilerplate::Formatter::new(boilerplate_output).indent("    ").trim(true),
             "{}",
             true
           )?;
@@ -393,7 +690,7 @@ mod tests {
         quote!(
           boilerplate_output.write_str(boilerplate_text[0].as_ref())?;
           write!(
-            ::boilerplate::Formatter::new(boilerplate_output, false, "    ", true),
+            ::boilerplate::Formatter::new(boilerplate_output).indent("    ").trim(true),
             "{}",
             true
           )?;
@@ -410,7 +707,15 @@ mod tests {
         "    $$ true\n",
         quote!(
           boilerplate_output.write_str(boilerplate_text[0].as_ref())?;
-          ::boilerplate::Format::format(&(true), boilerplate_output, "    ", true)?;
+          ({
+            use ::boilerplate::{Interpolate as _, InterpolateHtmlSafe as _};
+            (&::boilerplate::Interpolation(&(true))).interpolate(
+              boilerplate_output,
+              &::boilerplate::escaper::Html,
+              "    ",
+              true
+            )
+          })?;
           boilerplate_output.write_str("\n")?;
           boilerplate_output.write_str(boilerplate_text[1].as_ref())?;
         ),
@@ -420,21 +725,144 @@ mod tests {
         "    $$ true\n",
         quote!(
           boilerplate_output.write_str(boilerplate_text[0].as_ref())?;
-          ::boilerplate::Format::format(&(true), boilerplate_output, "    ", true)?;
+          ({
+            use ::boilerplate::{Interpolate as _, InterpolateHtmlSafe as _};
+            (&::boilerplate::Interpolation(&(true))).interpolate(
+              boilerplate_output,
+              &::boilerplate::escaper::Html,
+              "    ",
+              true
+            )
+          })?;
           boilerplate_output.write_str("\n")?;
         ),
       );
     }
   }
 
+  #[test]
+  fn filters() {
+    if cfg!(feature = "reload") {
+      assert_escape_body_eq(
+        "{{ x |> upper }}{{ x |> trusted }}",
+        quote!(
+          boilerplate_output.write_str(boilerplate_text[0].as_ref())?;
+          ({
+            use ::boilerplate::{Interpolate as _, InterpolateHtmlSafe as _};
+            (&::boilerplate::Interpolation(&(::boilerplate::filters::upper(&(x))))).interpolate(
+              boilerplate_output,
+              &::boilerplate::escaper::Html,
+              "",
+              false
+            )
+          })?;
+          boilerplate_output.write_str(boilerplate_text[1].as_ref())?;
+          write!(boilerplate_output, "{}", &(x))?;
+          boilerplate_output.write_str(boilerplate_text[2].as_ref())?;
+        ),
+      );
+    } else {
+      assert_escape_body_eq(
+        "{{ x |> upper }}{{ x |> trusted }}",
+        quote!(
+          ({
+            use ::boilerplate::{Interpolate as _, InterpolateHtmlSafe as _};
+            (&::boilerplate::Interpolation(&(::boilerplate::filters::upper(&(x))))).interpolate(
+              boilerplate_output,
+              &::boilerplate::escaper::Html,
+              "",
+              false
+            )
+          })?;
+          write!(boilerplate_output, "{}", &(x))?;
+        ),
+      );
+    }
+  }
+
+  #[test]
+  fn escape_attribute_interpolation() {
+    assert_escape_body_eq(
+      "<a title=\"{{ x }}\">",
+      quote!(
+        boilerplate_output.write_str(boilerplate_text[0].as_ref())?;
+        ::boilerplate::Format::format(
+          &(x),
+          boilerplate_output,
+          &::boilerplate::escaper::Html,
+          "",
+          false
+        )?;
+        boilerplate_output.write_str(boilerplate_text[1].as_ref())?;
+      ),
+    );
+  }
+
+  #[test]
+  fn html_safe_impl() {
+    assert_eq!(
+      Template {
+        axum: None,
+        delimiters: Delimiters::default(),
+        error: None,
+        escape: Some(Escape::Html),
+        generics: Generics::default(),
+        ident: Ident::new("Foo", Span::call_site()),
+        interpolation_size_hint: 16,
+        mime: mime::TEXT_HTML,
+        source: Source::Literal(LitStr::new("", Span::call_site())),
+      }
+      .html_safe_impl()
+      .to_string(),
+      quote!(impl ::boilerplate::HtmlSafe for Foo {}).to_string(),
+    );
+  }
+
+  #[test]
+  fn custom_escaper() {
+    let escape = Escape::Escaper(syn::parse_quote!(foo::Bar));
+
+    let body = Implementation::parse(
+      "$$ true",
+      &literal("$$ true"),
+      &boilerplate_parser::Delimiters::DEFAULT,
+      Some(&escape),
+      false,
+    )
+    .unwrap()
+    .body
+    .to_string();
+
+    let format = quote!(
+      ::boilerplate::Format::format(&(true), boilerplate_output, &foo::Bar, "", false)?;
+    );
+
+    if cfg!(feature = "reload") {
+      assert_eq!(
+        body,
+        quote!(
+          boilerplate_output.write_str(boilerplate_text[0].as_ref())?;
+          #format
+          boilerplate_output.write_str(boilerplate_text[1].as_ref())?;
+        )
+        .to_string(),
+      );
+    } else {
+      assert_eq!(body, format.to_string());
+    }
+  }
+
   #[test]
   fn axum_into_response_impl() {
     assert_eq!(
       Template {
         axum: Some(true),
-        escape: false,
+        delimiters: Delimiters::default(),
+        error: None,
+        escape: None,
         generics: Generics::default(),
         ident: Ident::new("Foo", Span::call_site()),
+        interpolation_size_hint: 16,
         mime: mime::TEXT_PLAIN,
         source: Source::Literal(LitStr::new("", Span::call_site())),
       }
@@ -443,12 +871,44 @@ mod tests {
       quote!(
         impl ::axum::response::IntoResponse for Foo {
           fn into_response(self) -> ::axum::response::Response {
-            extern crate alloc;
-            use alloc::string::ToString;
+            (
+              [(::axum::http::header::CONTENT_TYPE, "text/plain")],
+              <Self as ::boilerplate::Boilerplate>::render(&self).unwrap(),
+            ).into_response()
+          }
+        }
+      )
+      .to_string()
+    );
+  }
 
+  #[test]
+  fn fallible_axum_into_response_impl() {
+    assert_eq!(
+      Template {
+        axum: Some(true),
+        delimiters: Delimiters::default(),
+        error: Some(syn::parse_quote!(Error)),
+        escape: None,
+        generics: Generics::default(),
+        ident: Ident::new("Foo", Span::call_site()),
+        interpolation_size_hint: 16,
+        mime: mime::TEXT_PLAIN,
+        source: Source::Literal(LitStr::new("", Span::call_site())),
+      }
+      .axum_into_response_impl()
+      .to_string(),
+      quote!(
+        impl ::axum::response::IntoResponse for Foo {
+          fn into_response(self) -> ::axum::response::Response {
             (
               [(::axum::http::header::CONTENT_TYPE, "text/plain")],
-              self.to_string(),
+              match self.try_render() {
+                Ok(body) => body,
+                Err(_) => {
+                  return ::axum::http::StatusCode::INTERNAL_SERVER_ERROR.into_response();
+                }
+              },
             ).into_response()
           }
         }
@@ -0,0 +1,287 @@
+use super::*;
+
+/// Environment variable which sets the template directory.
+const TEMPLATES: &str = "BOILERPLATE_TEMPLATES";
+
+/// The directory which template paths are relative to.
+///
+/// Set with the `BOILERPLATE_TEMPLATES` environment variable, or with
+/// `templates` in the `[package.metadata.boilerplate]` table of `Cargo.toml`,
+/// and otherwise `templates`. Relative paths are relative to the directory
+/// containing `Cargo.toml`.
+pub(crate) fn templates(span: Span) -> Result<PathBuf, syn::Error> {
+  let manifest_dir = manifest_dir(span)?;
+
+  let templates = if let Some(templates) = std::env::var_os(TEMPLATES) {
+    PathBuf::from(templates)
+  } else {
+    let manifest = manifest_dir.join("Cargo.toml");
+
+    // crates built without cargo may not have a `Cargo.toml`
+    let src = match std::fs::read_to_string(&manifest) {
+      Ok(src) => src,
+      Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+      Err(err) => {
+        return Err(syn::Error::new(
+          span,
+          format!("failed to read `{}`: {err}", manifest.display()),
+        ));
+      }
+    };
+
+    metadata(&src)
+      .map_err(|message| syn::Error::new(span, format!("{}: {message}", manifest.display())))?
+      .unwrap_or_else(|| "templates".into())
+      .into()
+  };
+
+  Ok(manifest_dir.join(templates))
+}
+
+/// Read `BOILERPLATE_TEMPLATES` with `option_env!`, so that cargo rebuilds
+/// the crate when it changes.
+pub(crate) fn track_templates() -> TokenStream {
+  quote!(
+    const _: ::core::option::Option<&str> = ::core::option_env!(#TEMPLATES);
+  )
+}
+
+/// The directory containing the `Cargo.toml` of the crate being compiled.
+pub(crate) fn manifest_dir(span: Span) -> Result<PathBuf, syn::Error> {
+  let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").map_err(|err| {
+    syn::Error::new(
+      span,
+      format!("failed to get `CARGO_MANIFEST_DIR` environment variable: {err}"),
+    )
+  })?;
+
+  Ok(manifest_dir.into())
+}
+
+#[derive(Deserialize)]
+struct Manifest {
+  package: Option<Package>,
+}
+
+#[derive(Deserialize)]
+struct Package {
+  metadata: Option<Metadata>,
+}
+
+#[derive(Deserialize)]
+struct Metadata {
+  boilerplate: Option<Config>,
+}
+
+/// The `[package.metadata.boilerplate]` table.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Config {
+  templates: Option<String>,
+}
+
+/// Read `templates` from the `[package.metadata.boilerplate]` table of
+/// `Cargo.toml`.
+fn metadata(manifest: &str) -> Result<Option<String>, String> {
+  let manifest = toml::from_str::<Manifest>(manifest).map_err(|err| err.message().to_owned())?;
+
+  Ok(
+    manifest
+      .package
+      .and_then(|package| package.metadata)
+      .and_then(|metadata| metadata.boilerplate)
+      .and_then(|config| config.templates),
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, unindent::unindent};
+
+  #[track_caller]
+  fn case(manifest: &str, expected: Result<Option<&str>, &str>) {
+    assert_eq!(
+      metadata(&unindent(manifest)),
+      expected
+        .map(|templates| templates.map(ToOwned::to_owned))
+        .map_err(ToOwned::to_owned),
+    );
+  }
+
+  #[test]
+  fn missing() {
+    case(
+      r#"
+        [package]
+        name = "foo"
+      "#,
+      Ok(None),
+    );
+
+    case("", Ok(None));
+  }
+
+  #[test]
+  fn templates() {
+    case(
+      r#"
+        [package]
+        name = "foo"
+
+        [package.metadata.boilerplate]
+        templates = "../assets"
+      "#,
+      Ok(Some("../assets")),
+    );
+  }
+
+  #[test]
+  fn strings() {
+    case(
+      r"
+        [package.metadata.boilerplate]
+        templates = 'C:\assets'
+      ",
+      Ok(Some("C:\\assets")),
+    );
+
+    case(
+      r#"
+        [package.metadata.boilerplate]
+        templates = "foo\\bar\u00e9"
+      "#,
+      Ok(Some("foo\\bar\u{e9}")),
+    );
+
+    case(
+      r#"
+        [package.metadata.boilerplate]
+        templates = """
+        assets"""
+      "#,
+      Ok(Some("assets")),
+    );
+  }
+
+  #[test]
+  fn comments_and_whitespace() {
+    case(
+      r#"
+        [ package . metadata . boilerplate ] # comment
+        templates="assets" # comment
+      "#,
+      Ok(Some("assets")),
+    );
+  }
+
+  #[test]
+  fn quoted_keys() {
+    case(
+      r#"
+        [package.metadata."boilerplate"]
+        'templates' = "assets"
+      "#,
+      Ok(Some("assets")),
+    );
+  }
+
+  #[test]
+  fn other_table() {
+    case(
+      r#"
+        [package.metadata.boilerplate]
+        [dependencies]
+        templates = "assets"
+      "#,
+      Ok(None),
+    );
+  }
+
+  #[test]
+  fn dotted_key() {
+    case(
+      r#"
+        [package.metadata]
+        boilerplate.templates = "assets"
+      "#,
+      Ok(Some("assets")),
+    );
+  }
+
+  #[test]
+  fn other_metadata() {
+    case(
+      r#"
+        [package]
+        keywords = [
+          "boilerplate",
+          "templates",
+        ]
+
+        [package.metadata]
+        foo = { templates = "assets" }
+        docs = { rs = { all-features = true } }
+      "#,
+      Ok(None),
+    );
+  }
+
+  #[test]
+  fn inline_table() {
+    case(
+      r#"
+        [package.metadata]
+        boilerplate = { templates = "assets" }
+      "#,
+      Ok(Some("assets")),
+    );
+
+    case(
+      r#"
+        [package]
+        metadata = { boilerplate = { templates = "assets" } }
+      "#,
+      Ok(Some("assets")),
+    );
+  }
+
+  #[test]
+  fn unknown_key() {
+    case(
+      r#"
+        [package.metadata.boilerplate]
+        template = "assets"
+      "#,
+      Err("unknown field `template`, expected `templates`"),
+    );
+  }
+
+  #[test]
+  fn not_string() {
+    case(
+      r"
+        [package.metadata.boilerplate]
+        templates = 1
+      ",
+      Err("invalid type: integer `1`, expected a string"),
+    );
+
+    case(
+      r#"
+        [package.metadata.boilerplate.templates]
+        path = "assets"
+      "#,
+      Err("invalid type: map, expected a string"),
+    );
+  }
+
+  #[test]
+  fn invalid() {
+    case(
+      r"
+        [package
+      ",
+      Err("unclosed table, expected `]`"),
+    );
+  }
+}
@@ -0,0 +1,208 @@
+#![expect(clippy::needless_continue)]
+
+use super::*;
+
+/// An enum variant, set with `#[boilerplate(...)]` on the variant. Omitted
+/// attributes, other than `filename`, `path`, and `text`, are inherited from
+/// the enum.
+#[derive(FromVariant)]
+#[darling(attributes(boilerplate))]
+pub(crate) struct Variant {
+  pub(crate) delimiters: Option<Delimiters>,
+  pub(crate) escape: Option<escape::Attribute>,
+  fields: Fields<syn::Field>,
+  pub(crate) filename: Option<LitStr>,
+  pub(crate) ident: Ident,
+  pub(crate) interpolation_size_hint: Option<usize>,
+  pub(crate) path: Option<LitStr>,
+  pub(crate) text: Option<LitStr>,
+}
+
+impl Variant {
+  /// Names of the variant's fields. Tuple fields are bound to
+  /// `boilerplate_0`, `boilerplate_1`, and so on.
+  fn bindings(&self) -> Vec<Ident> {
+    self
+      .fields
+      .iter()
+      .enumerate()
+      .map(|(i, field)| {
+        field
+          .ident
+          .clone()
+          .unwrap_or_else(|| Ident::new(&format!("boilerplate_{i}"), Span::call_site()))
+      })
+      .collect()
+  }
+
+  /// Expression constructing the variant's struct from the bindings of the
+  /// variant's pattern.
+  pub(crate) fn constructor(&self, ident: &Ident, generics: &Generics) -> TokenStream {
+    let bindings = self.bindings();
+
+    let arguments = generics.params.iter().map(|param| match param {
+      GenericParam::Const(param) => param.ident.to_token_stream(),
+      GenericParam::Lifetime(param) => param.lifetime.to_token_stream(),
+      GenericParam::Type(param) => param.ident.to_token_stream(),
+    });
+
+    let path = quote!(#ident::<'_ #(, #arguments)*>);
+
+    if self.fields.style == Style::Struct {
+      quote!(#path { #(#bindings,)* boilerplate_phantom: ::core::marker::PhantomData })
+    } else {
+      quote!(#path(#(#bindings,)* ::core::marker::PhantomData))
+    }
+  }
+
+  /// Definition of the variant's struct, which has the same fields as the
+  /// variant, but borrowed, and a `PhantomData` field which uses all of the
+  /// struct's generic parameters.
+  pub(crate) fn definition(&self, ident: &Ident, generics: &Generics) -> TokenStream {
+    let where_clause = &generics.where_clause;
+
+    let phantom = generics.params.iter().filter_map(|param| match param {
+      GenericParam::Const(_) => None,
+      GenericParam::Lifetime(param) => {
+        let lifetime = &param.lifetime;
+        Some(quote!(&#lifetime ()))
+      }
+      GenericParam::Type(param) => {
+        let ident = &param.ident;
+        Some(quote!(&'boilerplate #ident))
+      }
+    });
+
+    let phantom = quote!(::core::marker::PhantomData<(&'boilerplate () #(, #phantom)*)>);
+
+    let generics = Self::generics(generics);
+
+    let types = self.fields.iter().map(|field| &field.ty);
+
+    if self.fields.style == Style::Struct {
+      let names = self.bindings();
+      quote! {
+        struct #ident #generics #where_clause {
+          #(#names: &'boilerplate #types,)*
+          boilerplate_phantom: #phantom,
+        }
+      }
+    } else {
+      quote! {
+        struct #ident #generics (#(&'boilerplate #types,)* #phantom,) #where_clause;
+      }
+    }
+  }
+
+  /// Generics of the variant's struct, which are the enum's generics, with an
+  /// additional `'boilerplate` lifetime for borrowed fields.
+  pub(crate) fn generics(generics: &Generics) -> Generics {
+    let mut generics = generics.clone();
+    generics.params.insert(0, syn::parse_quote!('boilerplate));
+    generics
+  }
+
+  /// Pattern matching the variant and binding its fields.
+  pub(crate) fn pattern(&self) -> TokenStream {
+    let ident = &self.ident;
+    let bindings = self.bindings();
+
+    match self.fields.style {
+      Style::Struct => quote!(Self::#ident { #(#bindings),* }),
+      Style::Tuple => quote!(Self::#ident(#(#bindings),*)),
+      Style::Unit => quote!(Self::#ident),
+    }
+  }
+
+  /// Identifier of the variant's struct.
+  pub(crate) fn struct_ident(&self, enumeration: &Ident) -> Ident {
+    Ident::new(
+      &format!("Boilerplate{enumeration}{}", self.ident),
+      self.ident.span(),
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, pretty_assertions::assert_eq};
+
+  fn variant(variant: syn::Variant) -> Variant {
+    Variant::from_variant(&variant).unwrap()
+  }
+
+  #[test]
+  fn tuple() {
+    let variant = variant(syn::parse_quote!(Foo(u8, T)));
+    let generics = syn::parse_quote!(<'a, T: Display>);
+    let ident = variant.struct_ident(&Ident::new("Bar", Span::call_site()));
+
+    assert_eq!(ident, "BoilerplateBarFoo");
+
+    assert_eq!(
+      variant.pattern().to_string(),
+      quote!(Self::Foo(boilerplate_0, boilerplate_1)).to_string(),
+    );
+
+    assert_eq!(
+      variant.constructor(&ident, &generics).to_string(),
+      quote!(BoilerplateBarFoo::<'_, 'a, T>(
+        boilerplate_0,
+        boilerplate_1,
+        ::core::marker::PhantomData
+      ))
+      .to_string(),
+    );
+
+    assert_eq!(
+      variant.definition(&ident, &generics).to_string(),
+      quote!(
+        struct BoilerplateBarFoo<'boilerplate, 'a, T: Display>(
+          &'boilerplate u8,
+          &'boilerplate T,
+          ::core::marker::PhantomData<(&'boilerplate (), &'a (), &'boilerplate T)>,
+        );
+      )
+      .to_string(),
+    );
+  }
+
+  #[test]
+  fn named() {
+    let variant = variant(syn::parse_quote!(Foo { a: u8 }));
+    let generics = Generics::default();
+    let ident = variant.struct_ident(&Ident::new("Bar", Span::call_site()));
+
+    assert_eq!(
+      variant.pattern().to_string(),
+      quote!(Self::Foo { a }).to_string(),
+    );
+
+    assert_eq!(
+      variant.constructor(&ident, &generics).to_string(),
+      quote!(BoilerplateBarFoo::<'_> {
+        a,
+        boilerplate_phantom: ::core::marker::PhantomData
+      })
+      .to_string(),
+    );
+
+    assert_eq!(
+      variant.definition(&ident, &generics).to_string(),
+      quote!(
+        struct BoilerplateBarFoo<'boilerplate> {
+          a: &'boilerplate u8,
+          boilerplate_phantom: ::core::marker::PhantomData<(&'boilerplate ())>,
+        }
+      )
+      .to_string(),
+    );
+  }
+
+  #[test]
+  fn unit() {
+    let variant = variant(syn::parse_quote!(Foo));
+
+    assert_eq!(variant.pattern().to_string(), quote!(Self::Foo).to_string());
+  }
+}
@@ -4,43 +4,77 @@ use super::*;
 pub enum Block {
   Code,
   CodeLine,
+  Comment,
+  CommentLine,
   Interpolation,
   InterpolationLine,
 }
 
 impl Block {
-  pub(crate) fn close_delimiter(self) -> &'static str {
+  pub(crate) fn close_delimiter<'a>(self, delimiters: &Delimiters<'a>) -> &'a str {
     match self {
-      Self::Code => "%}",
-      Self::CodeLine | Self::InterpolationLine => "\n",
-      Self::Interpolation => "}}",
+      Self::Code => delimiters.code.1,
+      Self::CodeLine | Self::CommentLine | Self::InterpolationLine => "\n",
+      Self::Comment => delimiters.comment.1,
+      Self::Interpolation => delimiters.interpolation.1,
     }
   }
 
-  pub(crate) fn from_rest(rest: &str) -> Option<Self> {
+  /// Find the close delimiter in `rest`. Literals and comments in code and
+  /// interpolation blocks are skipped, as are braces in interpolation blocks,
+  /// so that `{{ Foo { bar: "}}" } }}` is a single interpolation.
+  pub(crate) fn find_close(self, rest: &str, delimiters: &Delimiters) -> Option<usize> {
+    let close = self.close_delimiter(delimiters);
+
+    match self {
+      Self::Code => rust::find(rest, close, false),
+      Self::Interpolation => rust::find(rest, close, true),
+      Self::CodeLine | Self::Comment | Self::CommentLine | Self::InterpolationLine => {
+        rest.find(close)
+      }
+    }
+  }
+
+  /// The block whose open delimiter is the longest prefix of `rest`.
+  pub(crate) fn from_rest(rest: &str, delimiters: &Delimiters) -> Option<Self> {
     [
       Self::Code,
       Self::CodeLine,
+      Self::Comment,
+      Self::CommentLine,
       Self::Interpolation,
       Self::InterpolationLine,
     ]
     .into_iter()
-    .find(|block| rest.starts_with(block.open_delimiter()))
+    .filter(|block| {
+      let open = block.open_delimiter(delimiters);
+      !open.is_empty() && rest.starts_with(open)
+    })
+    .max_by_key(|block| block.open_delimiter(delimiters).len())
+  }
+
+  pub(crate) fn is_comment(self) -> bool {
+    match self {
+      Self::Code | Self::CodeLine | Self::Interpolation | Self::InterpolationLine => false,
+      Self::Comment | Self::CommentLine => true,
+    }
   }
 
   pub(crate) fn is_line(self) -> bool {
     match self {
-      Self::Code | Self::Interpolation => false,
-      Self::CodeLine | Self::InterpolationLine => true,
+      Self::Code | Self::Comment | Self::Interpolation => false,
+      Self::CodeLine | Self::CommentLine | Self::InterpolationLine => true,
     }
   }
 
-  pub(crate) fn open_delimiter(self) -> &'static str {
+  pub(crate) fn open_delimiter<'a>(self, delimiters: &Delimiters<'a>) -> &'a str {
     match self {
-      Self::Code => "{%",
-      Self::CodeLine => "%%",
-      Self::Interpolation => "{{",
-      Self::InterpolationLine => "$$",
+      Self::Code => delimiters.code.0,
+      Self::CodeLine => delimiters.code_line,
+      Self::Comment => delimiters.comment.0,
+      Self::CommentLine => delimiters.comment_line,
+      Self::Interpolation => delimiters.interpolation.0,
+      Self::InterpolationLine => delimiters.interpolation_line,
     }
   }
 
@@ -48,6 +82,8 @@ impl Block {
     match self {
       Self::Code => Token::Code { contents },
       Self::CodeLine => Token::CodeLine { contents, closed },
+      Self::Comment => Token::Comment { contents },
+      Self::CommentLine => Token::CommentLine { contents, closed },
       Self::Interpolation => Token::Interpolation { contents },
       Self::InterpolationLine => Token::InterpolationLine { contents, closed },
     }
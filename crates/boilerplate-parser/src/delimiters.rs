@@ -0,0 +1,31 @@
+/// Block delimiters.
+///
+/// Blocks are pairs of open and close delimiters, and line blocks are open
+/// delimiters which are closed by the end of the line. Blocks with empty open
+/// delimiters are disabled.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Delimiters<'a> {
+  pub code: (&'a str, &'a str),
+  pub code_line: &'a str,
+  pub comment: (&'a str, &'a str),
+  pub comment_line: &'a str,
+  pub interpolation: (&'a str, &'a str),
+  pub interpolation_line: &'a str,
+}
+
+impl Delimiters<'static> {
+  pub const DEFAULT: Self = Self {
+    code: ("{%", "%}"),
+    code_line: "%%",
+    comment: ("{{#", "#}}"),
+    comment_line: "$$#",
+    interpolation: ("{{", "}}"),
+    interpolation_line: "$$",
+  };
+}
+
+impl Default for Delimiters<'static> {
+  fn default() -> Self {
+    Self::DEFAULT
+  }
+}
@@ -0,0 +1,83 @@
+/// Directives are code blocks and code lines which are expanded before a
+/// template is compiled.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Directive<'src> {
+  /// `block name`, the start of a block which may be overridden by templates
+  /// which extend this one
+  Block(&'src str),
+  /// `endblock`, the end of a block
+  EndBlock,
+  /// `extends "path"`, which replaces the blocks of the template at `path`
+  /// with the blocks of this template
+  Extends(&'src str),
+  /// `include "path"`, which is replaced with the template at `path`
+  Include(&'src str),
+}
+
+impl<'src> Directive<'src> {
+  pub(crate) fn parse(code: &'src str) -> Option<Self> {
+    if code == "endblock" {
+      return Some(Self::EndBlock);
+    }
+
+    if let Some(rest) = code.strip_prefix("block") {
+      let name = rest.trim_start();
+      return (name.len() < rest.len() && is_identifier(name)).then_some(Self::Block(name));
+    }
+
+    if let Some(path) = path(code, "extends") {
+      return Some(Self::Extends(path));
+    }
+
+    path(code, "include").map(Self::Include)
+  }
+}
+
+fn is_identifier(s: &str) -> bool {
+  let mut chars = s.chars();
+
+  chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+    && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+fn path<'src>(code: &'src str, keyword: &str) -> Option<&'src str> {
+  let path = code
+    .strip_prefix(keyword)?
+    .trim_start()
+    .strip_prefix('"')?
+    .strip_suffix('"')?;
+
+  (!path.contains(['"', '\\'])).then_some(path)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse() {
+    #[track_caller]
+    fn case(code: &str, expected: Option<Directive>) {
+      assert_eq!(Directive::parse(code), expected);
+    }
+
+    case("block title", Some(Directive::Block("title")));
+    case("block  _foo1", Some(Directive::Block("_foo1")));
+    case("endblock", Some(Directive::EndBlock));
+    case(
+      "extends \"base.html\"",
+      Some(Directive::Extends("base.html")),
+    );
+    case("include \"a/b.html\"", Some(Directive::Include("a/b.html")));
+    case("include\"a\"", Some(Directive::Include("a")));
+    case("blocktitle", None);
+    case("block 1", None);
+    case("block a b", None);
+    case("block", None);
+    case("endblock title", None);
+    case("extends base.html", None);
+    case("include!(\"a\")", None);
+    case("includes \"a\"", None);
+    case("include \"a\\\"\"", None);
+  }
+}
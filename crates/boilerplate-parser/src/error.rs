@@ -2,7 +2,56 @@ use super::*;
 
 #[derive(Debug, PartialEq)]
 pub enum Error {
-  Unclosed(Block),
+  DuplicateBlock {
+    name: String,
+    span: Span,
+  },
+  Extends {
+    span: Span,
+  },
+  Include {
+    path: String,
+    span: Span,
+  },
+  /// An error in an included or extended template.
+  Nested {
+    error: Box<Error>,
+    path: String,
+  },
+  RecursiveInclude {
+    path: String,
+    span: Span,
+  },
+  Unclosed {
+    block: Block,
+    delimiter: String,
+    span: Span,
+  },
+  UnclosedBlock {
+    name: String,
+    span: Span,
+  },
+  UnmatchedEndblock {
+    span: Span,
+  },
+}
+
+impl Error {
+  /// Convert the span of `self`, which is in text which starts at `start`, to
+  /// a span in the text containing it.
+  pub(crate) fn shift(mut self, start: Span) -> Self {
+    match &mut self {
+      Self::DuplicateBlock { span, .. }
+      | Self::Extends { span }
+      | Self::Include { span, .. }
+      | Self::RecursiveInclude { span, .. }
+      | Self::Unclosed { span, .. }
+      | Self::UnclosedBlock { span, .. }
+      | Self::UnmatchedEndblock { span } => *span = span.shift(start),
+      Self::Nested { .. } => {}
+    }
+    self
+  }
 }
 
 impl std::error::Error for Error {}
@@ -10,7 +59,52 @@ impl std::error::Error for Error {}
 impl Display for Error {
   fn fmt(&self, f: &mut Formatter) -> fmt::Result {
     match self {
-      Self::Unclosed(block) => write!(f, "unmatched `{}`", block.open_delimiter()),
+      Self::DuplicateBlock { name, span } => write!(
+        f,
+        "duplicate block `{name}` at line {}, column {}",
+        span.line + 1,
+        span.column + 1,
+      ),
+      Self::Extends { span } => write!(
+        f,
+        "`extends` must come before all other blocks, at line {}, column {}",
+        span.line + 1,
+        span.column + 1,
+      ),
+      Self::Include { path, span } => write!(
+        f,
+        "failed to read template `{path}` at line {}, column {}",
+        span.line + 1,
+        span.column + 1,
+      ),
+      Self::Nested { error, path } => write!(f, "{error} in `{path}`"),
+      Self::RecursiveInclude { path, span } => write!(
+        f,
+        "recursive include of `{path}` at line {}, column {}",
+        span.line + 1,
+        span.column + 1,
+      ),
+      Self::Unclosed {
+        delimiter, span, ..
+      } => write!(
+        f,
+        "unmatched `{}` at line {}, column {}",
+        delimiter,
+        span.line + 1,
+        span.column + 1,
+      ),
+      Self::UnclosedBlock { name, span } => write!(
+        f,
+        "unclosed block `{name}` at line {}, column {}",
+        span.line + 1,
+        span.column + 1,
+      ),
+      Self::UnmatchedEndblock { span } => write!(
+        f,
+        "unmatched `endblock` at line {}, column {}",
+        span.line + 1,
+        span.column + 1,
+      ),
     }
   }
 }
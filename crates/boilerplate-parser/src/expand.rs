@@ -0,0 +1,1114 @@
+use {super::*, std::collections::BTreeMap};
+
+/// Expand directives in `src`.
+///
+/// Include directives, for example `{% include "nav.html" %}`, are replaced by
+/// the contents of the included template, as returned by `read`, with
+/// directives in the included template also expanded.
+///
+/// If the include directive is preceded only by whitespace on its line, that
+/// whitespace is added to the beginning of each line of text in the included
+/// template. The final newline of templates included by code blocks is
+/// removed, since it is usually followed by the newline after the code block.
+///
+/// Templates which begin with an extends directive, for example
+/// `{% extends "base.html" %}`, are replaced by the extended template, with
+/// each block of the extended template, delimited by `{% block name %}` and
+/// `{% endblock %}`, replaced by the block of the same name in the extending
+/// template, if any. Content outside of blocks in the extending template is
+/// ignored.
+///
+/// Block directives which are alone on their line are removed along with
+/// their line.
+///
+/// Each template is parsed separately, so tokens in an included or extended
+/// template never run together with tokens in the template which includes or
+/// extends it.
+///
+/// If `read` returns `None`, expansion fails with `Error::Include`.
+pub fn expand(
+  src: &str,
+  delimiters: &Delimiters,
+  read: &mut dyn FnMut(&str) -> Option<String>,
+) -> Result<Expansion, Error> {
+  let mut expander = Expander {
+    blocks: BTreeMap::new(),
+    delimiters,
+    failed: false,
+    paths: Vec::new(),
+    read,
+    stack: Vec::new(),
+  };
+
+  let mut expansion = expander.expand(src, None, Span::default(), "", true)?;
+
+  expansion.paths = expander.paths;
+
+  Ok(expansion)
+}
+
+/// The body of a block in a template which extends another template.
+struct Body {
+  /// index of the path of the template containing the block
+  path: Option<usize>,
+  src: String,
+  /// location of the start of the body in the template containing it
+  start: Span,
+}
+
+struct Expander<'a, 'b> {
+  /// blocks of extending templates, which replace blocks of the same name
+  blocks: BTreeMap<String, Body>,
+  delimiters: &'a Delimiters<'a>,
+  /// whether a template has failed to expand
+  failed: bool,
+  /// paths of included and extended templates
+  paths: Vec<String>,
+  read: &'b mut dyn FnMut(&str) -> Option<String>,
+  /// paths of the templates currently being expanded
+  stack: Vec<String>,
+}
+
+impl Expander<'_, '_> {
+  /// Expand `src`, which is found at `start` in the template at index `path`
+  /// of `self.paths`, or in the template being expanded, if `path` is `None`.
+  fn expand(
+    &mut self,
+    src: &str,
+    path: Option<usize>,
+    start: Span,
+    indent: &str,
+    at_line_start: bool,
+  ) -> Result<Expansion, Error> {
+    let result = self.expand_template(src, path, start, indent, at_line_start);
+
+    // errors are returned through the templates which contain the template
+    // in which they occurred, so the first template to fail is the one in
+    // which the error occurred
+    if self.failed {
+      return result;
+    }
+
+    result.map_err(|error| {
+      self.failed = true;
+
+      match path {
+        Some(path) => Error::Nested {
+          error: Box::new(error),
+          path: self.paths[path].clone(),
+        },
+        None => error,
+      }
+    })
+  }
+
+  fn expand_template(
+    &mut self,
+    src: &str,
+    path: Option<usize>,
+    start: Span,
+    indent: &str,
+    at_line_start: bool,
+  ) -> Result<Expansion, Error> {
+    let tokens = Token::parse_spanned(src, self.delimiters).map_err(|err| err.shift(start))?;
+
+    let first = tokens.iter().position(|(token, _)| match token {
+      Token::Text { contents, .. } => !contents.trim().is_empty(),
+      Token::Comment { .. } | Token::CommentLine { .. } => false,
+      _ => true,
+    });
+
+    if let Some(first) = first
+      && let Some(Directive::Extends(extended)) = tokens[first].0.directive()
+    {
+      return self.extend(
+        src,
+        path,
+        start,
+        &tokens,
+        first,
+        extended,
+        indent,
+        at_line_start,
+      );
+    }
+
+    let mut output = Output::new(indent, at_line_start);
+
+    // names of blocks defined by this template
+    let mut defined = Vec::new();
+
+    // default blocks which have not yet been closed
+    let mut open = Vec::new();
+
+    let mut i = 0;
+
+    while i < tokens.len() {
+      let (token, span) = tokens[i];
+
+      match token.directive() {
+        None => output.token(token, path, span.shift(start), self.delimiters),
+        Some(Directive::Block(name)) => {
+          if defined.contains(&name) {
+            return Err(Error::DuplicateBlock {
+              name: name.into(),
+              span: span.shift(start),
+            });
+          }
+
+          defined.push(name);
+
+          output.open(token);
+
+          if let Some(body) = self.blocks.remove(name) {
+            let end = end(&tokens, i).map_err(|err| err.shift(start))?;
+            let (at_line_start, indent) = output.site();
+            let expanded = self.expand(&body.src, body.path, body.start, &indent, at_line_start);
+            self.blocks.insert(name.into(), body);
+            output.insert(expanded?);
+            i = end;
+            output.close(tokens[end].0, standalone(&tokens, end));
+          } else {
+            open.push((name, span));
+            output.close(token, standalone(&tokens, i));
+          }
+        }
+        Some(Directive::EndBlock) => {
+          if open.pop().is_none() {
+            return Err(Error::UnmatchedEndblock {
+              span: span.shift(start),
+            });
+          }
+
+          output.open(token);
+          output.close(token, standalone(&tokens, i));
+        }
+        Some(Directive::Extends(_)) => {
+          return Err(Error::Extends {
+            span: span.shift(start),
+          });
+        }
+        Some(Directive::Include(included)) => {
+          let mut src = self.read(included, span.shift(start))?;
+
+          if matches!(token, Token::Code { .. }) && src.ends_with('\n') {
+            src.pop();
+          }
+
+          output.open(token);
+          let (at_line_start, indent) = output.site();
+          let expanded = self.nested(included, &src, &indent, at_line_start)?;
+          output.insert(expanded);
+          output.close(token, false);
+        }
+      }
+
+      i += 1;
+    }
+
+    if let Some((name, span)) = open.pop() {
+      return Err(Error::UnclosedBlock {
+        name: name.into(),
+        span: span.shift(start),
+      });
+    }
+
+    Ok(output.finish())
+  }
+
+  #[expect(clippy::too_many_arguments)]
+  fn extend(
+    &mut self,
+    src: &str,
+    path: Option<usize>,
+    start: Span,
+    tokens: &[(Token, Span)],
+    first: usize,
+    extended: &str,
+    indent: &str,
+    at_line_start: bool,
+  ) -> Result<Expansion, Error> {
+    let mut defined = Vec::new();
+    let mut inserted = Vec::new();
+    let mut locator = Locator::new(src);
+
+    let mut i = first + 1;
+
+    while i < tokens.len() {
+      let (token, span) = tokens[i];
+
+      match token.directive() {
+        Some(Directive::Block(name)) => {
+          let end = end(tokens, i).map_err(|err| err.shift(start))?;
+
+          if defined.contains(&name) {
+            return Err(Error::DuplicateBlock {
+              name: name.into(),
+              span: span.shift(start),
+            });
+          }
+
+          defined.push(name);
+
+          // blocks of templates which extend this template take precedence
+          if !self.blocks.contains_key(name) {
+            let body = body(src, tokens, i, end);
+
+            self.blocks.insert(
+              name.into(),
+              Body {
+                path,
+                src: src[body.clone()].into(),
+                start: locator.span(body.start, body.end).shift(start),
+              },
+            );
+
+            inserted.push(name);
+          }
+
+          i = end;
+        }
+        Some(Directive::EndBlock) => {
+          return Err(Error::UnmatchedEndblock {
+            span: span.shift(start),
+          });
+        }
+        Some(Directive::Extends(_)) => {
+          return Err(Error::Extends {
+            span: span.shift(start),
+          });
+        }
+        Some(Directive::Include(_)) | None => {}
+      }
+
+      i += 1;
+    }
+
+    let src = self.read(extended, tokens[first].1.shift(start))?;
+
+    let expanded = self.nested(extended, &src, indent, at_line_start)?;
+
+    for name in inserted {
+      self.blocks.remove(name);
+    }
+
+    Ok(expanded)
+  }
+
+  fn nested(
+    &mut self,
+    path: &str,
+    src: &str,
+    indent: &str,
+    at_line_start: bool,
+  ) -> Result<Expansion, Error> {
+    let index = if let Some(index) = self.paths.iter().position(|other| other == path) {
+      index
+    } else {
+      self.paths.push(path.into());
+      self.paths.len() - 1
+    };
+
+    self.stack.push(path.into());
+    let expanded = self.expand(src, Some(index), Span::default(), indent, at_line_start);
+    self.stack.pop();
+    expanded
+  }
+
+  fn read(&mut self, path: &str, span: Span) -> Result<String, Error> {
+    if self.stack.iter().any(|included| included == path) {
+      return Err(Error::RecursiveInclude {
+        path: path.into(),
+        span,
+      });
+    }
+
+    (self.read)(path).ok_or_else(|| Error::Include {
+      path: path.into(),
+      span,
+    })
+  }
+}
+
+/// Expanded template output.
+struct Output<'a> {
+  /// indentation of the template being expanded
+  indent: &'a str,
+  /// whether `indent` has been written to the current line
+  indented: bool,
+  /// offset of the current line, if it is the start of a line
+  line: Option<usize>,
+  /// whether to skip whitespace up to and including the next newline
+  skip: bool,
+  string: String,
+  /// offset of the preceding text, if any
+  text: Option<usize>,
+  tokens: Vec<Expanded>,
+  /// whether to trim whitespace from the start of the next text
+  trim: bool,
+}
+
+impl<'a> Output<'a> {
+  fn new(indent: &'a str, at_line_start: bool) -> Self {
+    Self {
+      indent,
+      indented: false,
+      line: at_line_start.then_some(0),
+      skip: false,
+      string: String::new(),
+      text: None,
+      tokens: Vec::new(),
+      trim: false,
+    }
+  }
+
+  fn finish(self) -> Expansion {
+    Expansion {
+      paths: Vec::new(),
+      src: self.string,
+      tokens: self.tokens,
+    }
+  }
+
+  /// Write `indent` if at the start of a line which has not been indented.
+  fn indent(&mut self) {
+    if self.line.is_some() && !self.indented {
+      self.string.push_str(self.indent);
+      self.indented = true;
+    }
+  }
+
+  /// Write a token which is not a directive, found at `span` in the template
+  /// at index `path`.
+  fn token(&mut self, token: Token, path: Option<usize>, span: Span, delimiters: &Delimiters) {
+    let start = self.string.len();
+
+    let contents = match token {
+      Token::Text { mut contents, .. } => {
+        let empty = contents.is_empty();
+
+        if self.skip
+          && let Some(rest) = contents.trim_start_matches([' ', '\t']).strip_prefix('\n')
+        {
+          contents = rest;
+        }
+
+        if self.trim {
+          contents = contents.trim_start();
+        }
+
+        for (i, segment) in contents.split('\n').enumerate() {
+          if i > 0 {
+            self.string.push('\n');
+            self.line = Some(self.string.len());
+            self.indented = false;
+          }
+
+          if !segment.is_empty() {
+            self.indent();
+            self.string.push_str(segment);
+          }
+        }
+
+        self.text = Some(start);
+
+        // text which was removed entirely is dropped, unless reloading, since
+        // whether text is removed may differ between versions of a template
+        if self.string.len() == start && !empty && !cfg!(feature = "reload") {
+          self.skip = false;
+          self.trim = false;
+          return;
+        }
+
+        start..self.string.len()
+      }
+      Token::Code { .. } | Token::Comment { .. } => {
+        self.write(token, delimiters);
+        self.text = None;
+        Self::contents(start, token, delimiters)
+      }
+      Token::CodeLine { closed, .. } | Token::CommentLine { closed, .. } => {
+        self.write(token, delimiters);
+        self.line = closed.then_some(self.string.len());
+        self.indented = false;
+        self.text = None;
+        Self::contents(start, token, delimiters)
+      }
+      Token::Escape { .. } | Token::Interpolation { .. } | Token::InterpolationLine { .. } => {
+        self.indent();
+
+        let start = self.string.len();
+
+        self.write(token, delimiters);
+
+        if let Token::InterpolationLine { closed: true, .. } = token {
+          self.line = Some(self.string.len());
+          self.indented = false;
+        }
+
+        self.text = None;
+        Self::contents(start, token, delimiters)
+      }
+    };
+
+    self.tokens.push(Expanded {
+      contents,
+      path,
+      span,
+      token: token.with_contents(""),
+    });
+
+    self.skip = false;
+    self.trim = false;
+  }
+
+  /// The range of the contents of `token`, which was written at `start`.
+  fn contents(start: usize, token: Token, delimiters: &Delimiters) -> Range<usize> {
+    let start = start
+      + token
+        .block()
+        .map_or(0, |block| block.open_delimiter(delimiters).len());
+    start..start + token.contents().len()
+  }
+
+  fn write(&mut self, token: Token, delimiters: &Delimiters) {
+    self.string.push_str(&token.display(delimiters).to_string());
+  }
+
+  /// Truncate the output to `len`, which must not be before the last token
+  /// which is not text.
+  fn truncate(&mut self, len: usize) {
+    self.string.truncate(len);
+
+    while let Some(last) = self.tokens.last_mut()
+      && last.contents.end > len
+    {
+      if last.contents.start < len {
+        last.contents.end = len;
+      } else {
+        self.tokens.pop();
+      }
+    }
+  }
+
+  /// Start a directive, trimming the preceding text if `token` has a trim
+  /// marker.
+  fn open(&mut self, token: Token) {
+    if token.trim_before()
+      && let Some(start) = self.text
+    {
+      self.truncate(start + self.string[start..].trim_end().len());
+      self.line = self.line.filter(|&line| line <= self.string.len());
+    }
+  }
+
+  /// The position of a directive. If the directive is preceded only by
+  /// whitespace on its line, the whitespace is removed, and added to the
+  /// returned indentation.
+  fn site(&mut self) -> (bool, String) {
+    match self.line {
+      Some(start) if is_blank(&self.string[start..]) => {
+        let whitespace = &self.string[start + if self.indented { self.indent.len() } else { 0 }..];
+        let indent = format!("{}{whitespace}", self.indent);
+        self.truncate(start);
+        self.indented = false;
+        (true, indent)
+      }
+      _ => (false, self.indent.to_owned()),
+    }
+  }
+
+  /// Write the expansion of a directive.
+  fn insert(&mut self, expanded: Expansion) {
+    let offset = self.string.len();
+
+    self.string.push_str(&expanded.src);
+
+    self
+      .tokens
+      .extend(expanded.tokens.into_iter().map(|mut token| {
+        token.contents = token.contents.start + offset..token.contents.end + offset;
+        token
+      }));
+
+    if expanded.src.ends_with('\n') {
+      self.line = Some(self.string.len());
+    } else if !expanded.src.is_empty() {
+      self.line = None;
+    }
+
+    self.indented = false;
+  }
+
+  /// End a directive. If the directive is alone on its line, the line is
+  /// removed.
+  fn close(&mut self, token: Token, standalone: bool) {
+    self.skip = false;
+
+    if standalone
+      && let Some(start) = self.line
+      && is_blank(&self.string[start..])
+    {
+      self.truncate(start);
+      self.indented = false;
+      self.skip = !matches!(token, Token::CodeLine { .. });
+    }
+
+    self.text = None;
+    self.trim = token.trim_after();
+  }
+}
+
+/// The range of the body of the block opened at `open` and closed at `close`,
+/// without the lines of block directives which are alone on their line.
+fn body(src: &str, tokens: &[(Token, Span)], open: usize, close: usize) -> Range<usize> {
+  let (open_token, open_span) = tokens[open];
+  let (close_token, close_span) = tokens[close];
+
+  let mut body = &src[open_span.end..close_span.start];
+
+  if standalone(tokens, open) && !matches!(open_token, Token::CodeLine { .. }) {
+    body = body
+      .trim_start_matches([' ', '\t'])
+      .strip_prefix('\n')
+      .unwrap_or(body);
+  }
+
+  if standalone(tokens, close) {
+    body = body.trim_end_matches([' ', '\t']);
+  }
+
+  if open_token.trim_after() {
+    body = body.trim_start();
+  }
+
+  if close_token.trim_before() {
+    body = body.trim_end();
+  }
+
+  let start = body.as_ptr() as usize - src.as_ptr() as usize;
+
+  start..start + body.len()
+}
+
+/// Find the index of the `endblock` directive which closes the block opened
+/// at `open`.
+fn end(tokens: &[(Token, Span)], open: usize) -> Result<usize, Error> {
+  let mut depth = 0usize;
+
+  for (i, (token, _)) in tokens.iter().enumerate().skip(open) {
+    match token.directive() {
+      Some(Directive::Block(_)) => depth += 1,
+      Some(Directive::EndBlock) => {
+        depth -= 1;
+        if depth == 0 {
+          return Ok(i);
+        }
+      }
+      _ => {}
+    }
+  }
+
+  let (token, span) = tokens[open];
+
+  let Some(Directive::Block(name)) = token.directive() else {
+    unreachable!();
+  };
+
+  Err(Error::UnclosedBlock {
+    name: name.into(),
+    span,
+  })
+}
+
+fn is_blank(s: &str) -> bool {
+  s.chars().all(|c| c == ' ' || c == '\t')
+}
+
+/// Whether the token at `i` is preceded and followed only by whitespace on
+/// its line.
+fn standalone(tokens: &[(Token, Span)], i: usize) -> bool {
+  fn ends_line(token: Token) -> bool {
+    matches!(
+      token,
+      Token::CodeLine { closed: true, .. }
+        | Token::CommentLine { closed: true, .. }
+        | Token::InterpolationLine { closed: true, .. }
+    )
+  }
+
+  let before = match i.checked_sub(1).map(|i| tokens[i].0) {
+    None => true,
+    Some(Token::Text { contents, .. }) => match contents.rfind('\n') {
+      Some(newline) => is_blank(&contents[newline + 1..]),
+      None => is_blank(contents) && (i < 2 || ends_line(tokens[i - 2].0)),
+    },
+    Some(token) => ends_line(token),
+  };
+
+  let after = match tokens[i].0 {
+    Token::CodeLine { .. } => true,
+    _ => match tokens.get(i + 1).map(|(token, _)| *token) {
+      None => true,
+      Some(Token::Text { contents, .. }) => {
+        let rest = contents.trim_start_matches([' ', '\t']);
+        rest.starts_with('\n') || (rest.is_empty() && i + 2 == tokens.len())
+      }
+      Some(_) => false,
+    },
+  };
+
+  before && after
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, pretty_assertions::assert_eq};
+
+  #[track_caller]
+  fn case(src: &str, partials: &[(&str, &str)], expected: &str) {
+    let actual = expand(src, &Delimiters::DEFAULT, &mut |path| {
+      partials
+        .iter()
+        .find(|(name, _)| *name == path)
+        .map(|(_, contents)| (*contents).into())
+    })
+    .unwrap()
+    .to_string();
+    assert_eq!(actual, expected);
+  }
+
+  #[test]
+  fn no_includes() {
+    case(
+      "foo {{ bar }} {% baz %} {{{{",
+      &[],
+      "foo {{ bar }} {% baz %} {{{{",
+    );
+  }
+
+  #[test]
+  fn block() {
+    case(
+      "a {% include \"b.txt\" %} c",
+      &[("b.txt", "{{ b }}\n")],
+      "a {{ b }} c",
+    );
+  }
+
+  #[test]
+  fn line() {
+    case(
+      "a\n%% include \"b.txt\"\nc",
+      &[("b.txt", "{{ b }}\n")],
+      "a\n{{ b }}\nc",
+    );
+  }
+
+  #[test]
+  fn nested() {
+    case(
+      "{% include \"a\" %}",
+      &[("a", "a {% include \"b\" %} a"), ("b", "b")],
+      "a b a",
+    );
+  }
+
+  #[test]
+  fn indentation() {
+    case(
+      "<ul>\n  {% include \"items\" %}\n</ul>\n",
+      &[(
+        "items",
+        "<li>a</li>\n<li>{{ b }}</li>\n\n$$ c\n<li>d</li>\n",
+      )],
+      "<ul>\n  <li>a</li>\n  <li>{{ b }}</li>\n\n  $$ c\n  <li>d</li>\n</ul>\n",
+    );
+    case(
+      "<ul>\n  %% include \"items\"\n</ul>\n",
+      &[("items", "%% for x in y {\n<li>{{ x }}</li>\n%% }\n")],
+      "<ul>\n%% for x in y {\n  <li>{{ x }}</li>\n%% }\n</ul>\n",
+    );
+    case(
+      "<body>\n  {% include \"a\" %}\n</body>",
+      &[
+        ("a", "<div>\n  {% include \"b\" %}\n</div>\n"),
+        ("b", "b\nb\n"),
+      ],
+      "<body>\n  <div>\n    b\n    b\n  </div>\n</body>",
+    );
+    case("x {% include \"a\" %}", &[("a", "a\na")], "x a\na");
+  }
+
+  #[test]
+  fn trim() {
+    case("a\n  {%- include \"b\" -%}  \nc", &[("b", "b")], "abc");
+  }
+
+  #[test]
+  fn not_includes() {
+    case(
+      "{% include!(\"a\") %}{% includes \"a\" %}{% include \"a\\\"\" %}{{ include \"a\" }}",
+      &[],
+      "{% include!(\"a\") %}{% includes \"a\" %}{% include \"a\\\"\" %}{{ include \"a\" }}",
+    );
+  }
+
+  const BASE: &str = "<title>{% block title %}Default{% endblock %}</title>
+<body>
+  {% block content %}
+  <p>default</p>
+  {% endblock %}
+</body>
+";
+
+  #[test]
+  fn default_blocks() {
+    case(
+      BASE,
+      &[],
+      "<title>Default</title>\n<body>\n  <p>default</p>\n</body>\n",
+    );
+  }
+
+  #[test]
+  fn extends() {
+    case(
+      "{% extends \"base\" %}
+{% block title %}Home{% endblock %}
+{% block content %}
+<p>{{ x }}</p>
+<p>b</p>
+{% endblock %}
+",
+      &[("base", BASE)],
+      "<title>Home</title>\n<body>\n  <p>{{ x }}</p>\n  <p>b</p>\n</body>\n",
+    );
+    case(
+      "{{# comment #}}\n{% extends \"base\" %}ignored{% block content %}{% endblock %}",
+      &[("base", BASE)],
+      "<title>Default</title>\n<body>\n</body>\n",
+    );
+    case(
+      "{% extends \"base\" %}{% block content %}inline{% endblock %}",
+      &[("base", BASE)],
+      "<title>Default</title>\n<body>\n  inline\n</body>\n",
+    );
+  }
+
+  #[test]
+  fn extends_lines() {
+    case(
+      "%% extends \"base\"\n%% block content\nb\n%% endblock\n",
+      &[("base", "a\n%% block content\n%% endblock\nc\n")],
+      "a\nb\nc\n",
+    );
+  }
+
+  #[test]
+  fn extends_multiple_levels() {
+    case(
+      "{% extends \"b\" %}{% block y %}c{% endblock %}{% block z %}c{% endblock %}",
+      &[
+        (
+          "a",
+          "{% block x %}a{% endblock %}{% block y %}a{% endblock %}{% block z %}a{% endblock %}",
+        ),
+        (
+          "b",
+          "{% extends \"a\" %}{% block x %}b{% endblock %}{% block y %}b{% endblock %}",
+        ),
+      ],
+      "bcc",
+    );
+  }
+
+  #[test]
+  fn nested_blocks() {
+    let base = "{% block a %}<{% block b %}b{% endblock %}>{% endblock %}";
+    case(base, &[], "<b>");
+    case(
+      "{% extends \"base\" %}{% block b %}c{% endblock %}",
+      &[("base", base)],
+      "<c>",
+    );
+    case(
+      "{% extends \"base\" %}{% block a %}[{% block b %}c{% endblock %}]{% endblock %}",
+      &[("base", base)],
+      "[c]",
+    );
+  }
+
+  #[test]
+  fn tokens() {
+    let expansion = expand(
+      "{% include \"a\" %}{{ x }}{{{{ y }}",
+      &Delimiters::DEFAULT,
+      &mut |_| Some("x{".into()),
+    )
+    .unwrap();
+
+    assert_eq!(expansion.to_string(), "x{{{ x }}{{{{ y }}");
+
+    assert_eq!(
+      expansion.tokens(),
+      [
+        #[cfg(feature = "reload")]
+        Token::Text {
+          contents: "",
+          index: 0,
+        },
+        Token::Text {
+          contents: "x{",
+          index: 0,
+        },
+        #[cfg(feature = "reload")]
+        Token::Text {
+          contents: "",
+          index: 0,
+        },
+        Token::Interpolation { contents: " x " },
+        Token::Text {
+          contents: "",
+          index: 1,
+        },
+        Token::Escape { contents: "{{" },
+        Token::Text {
+          contents: " y }}",
+          index: 1,
+        },
+      ],
+    );
+
+    assert_eq!(
+      expansion.origins(),
+      [
+        #[cfg(feature = "reload")]
+        Origin {
+          path: None,
+          span: Span::default(),
+        },
+        Origin {
+          path: Some("a"),
+          span: Span {
+            column: 0,
+            end: 2,
+            line: 0,
+            start: 0,
+          },
+        },
+        #[cfg(feature = "reload")]
+        Origin {
+          path: None,
+          span: Span {
+            column: 17,
+            end: 17,
+            line: 0,
+            start: 17,
+          },
+        },
+        Origin {
+          path: None,
+          span: Span {
+            column: 17,
+            end: 24,
+            line: 0,
+            start: 17,
+          },
+        },
+        Origin {
+          path: None,
+          span: Span {
+            column: 24,
+            end: 24,
+            line: 0,
+            start: 24,
+          },
+        },
+        Origin {
+          path: None,
+          span: Span {
+            column: 24,
+            end: 28,
+            line: 0,
+            start: 24,
+          },
+        },
+        Origin {
+          path: None,
+          span: Span {
+            column: 28,
+            end: 33,
+            line: 0,
+            start: 28,
+          },
+        },
+      ],
+    );
+  }
+
+  #[test]
+  fn block_errors() {
+    #[track_caller]
+    fn case(src: &str, expected: &str) {
+      assert_eq!(
+        expand(src, &Delimiters::DEFAULT, &mut |_| Some(String::new()))
+          .unwrap_err()
+          .to_string(),
+        expected,
+      );
+    }
+
+    case("a\n{% block a %}", "unclosed block `a` at line 2, column 1");
+    case("{% endblock %}", "unmatched `endblock` at line 1, column 1");
+    case(
+      "{% extends \"a\" %}{% endblock %}",
+      "unmatched `endblock` at line 1, column 18",
+    );
+    case(
+      "a{% extends \"a\" %}",
+      "`extends` must come before all other blocks, at line 1, column 2",
+    );
+    case(
+      "{% extends \"a\" %}{% block a %}{% endblock %}{% block a %}{% endblock %}",
+      "duplicate block `a` at line 1, column 45",
+    );
+    case(
+      "{% block a %}{% endblock %}\n{% block a %}{% endblock %}",
+      "duplicate block `a` at line 2, column 1",
+    );
+  }
+
+  #[test]
+  fn nested_errors() {
+    #[track_caller]
+    fn case(src: &str, templates: &[(&str, &str)], expected: Error) {
+      assert_eq!(
+        expand(src, &Delimiters::DEFAULT, &mut |path| {
+          templates
+            .iter()
+            .find(|(name, _src)| *name == path)
+            .map(|(_name, src)| (*src).into())
+        }),
+        Err(expected),
+      );
+    }
+
+    let span = Span {
+      column: 0,
+      end: 18,
+      line: 1,
+      start: 5,
+    };
+
+    case(
+      "{% include \"a\" %}",
+      &[("a", "text\n{% block q %}")],
+      Error::Nested {
+        error: Box::new(Error::UnclosedBlock {
+          name: "q".into(),
+          span,
+        }),
+        path: "a".into(),
+      },
+    );
+
+    case(
+      "{% include \"a\" %}",
+      &[("a", "{% include \"b\" %}"), ("b", "text\n{% endblock %}")],
+      Error::Nested {
+        error: Box::new(Error::UnmatchedEndblock {
+          span: Span {
+            column: 0,
+            end: 19,
+            line: 1,
+            start: 5,
+          },
+        }),
+        path: "b".into(),
+      },
+    );
+
+    case(
+      "{% extends \"base\" %}",
+      &[(
+        "base",
+        "text\n{% block a %}{% endblock %}{% block a %}{% endblock %}",
+      )],
+      Error::Nested {
+        error: Box::new(Error::DuplicateBlock {
+          name: "a".into(),
+          span: Span {
+            column: 27,
+            end: 45,
+            line: 1,
+            start: 32,
+          },
+        }),
+        path: "base".into(),
+      },
+    );
+
+    // errors in blocks are located in the template containing the block
+    case(
+      "{% extends \"base\" %}{% block a %}\n{{ {% endblock %}",
+      &[("base", "{% block a %}{% endblock %}")],
+      Error::Unclosed {
+        block: Block::Interpolation,
+        delimiter: "{{".into(),
+        span: Span {
+          column: 0,
+          end: 36,
+          line: 1,
+          start: 34,
+        },
+      },
+    );
+
+    case(
+      "{% include \"a\" %}",
+      &[("a", "{% include \"a\" %}")],
+      Error::Nested {
+        error: Box::new(Error::RecursiveInclude {
+          path: "a".into(),
+          span: Span {
+            column: 0,
+            end: 17,
+            line: 0,
+            start: 0,
+          },
+        }),
+        path: "a".into(),
+      },
+    );
+  }
+
+  #[test]
+  fn errors() {
+    assert_eq!(
+      expand("a\n {% include \"a\" %}", &Delimiters::DEFAULT, &mut |_| {
+        None
+      }),
+      Err(Error::Include {
+        path: "a".into(),
+        span: Span {
+          column: 1,
+          end: 20,
+          line: 1,
+          start: 3,
+        },
+      }),
+    );
+    assert_eq!(
+      expand("{% include \"a\" %}", &Delimiters::DEFAULT, &mut |path| {
+        Some(format!(
+          "%% include \"{}\"",
+          if path == "a" { "b" } else { "a" }
+        ))
+      },)
+      .unwrap_err()
+      .to_string(),
+      "recursive include of `a` at line 1, column 1 in `b`",
+    );
+  }
+}
@@ -0,0 +1,93 @@
+use super::*;
+
+/// A template with its directives expanded, returned by [`expand`].
+///
+/// Tokens are kept as they were parsed from the template in which they
+/// appear, so that tokens from different templates are never combined.
+#[derive(Debug, Default, PartialEq)]
+pub struct Expansion {
+  pub(crate) paths: Vec<String>,
+  pub(crate) src: String,
+  pub(crate) tokens: Vec<Expanded>,
+}
+
+/// A token in an expansion.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Expanded {
+  /// range of the token's contents in `Expansion::src`
+  pub(crate) contents: Range<usize>,
+  /// index of the path of the template containing the token, if not the
+  /// template being expanded
+  pub(crate) path: Option<usize>,
+  /// span of the token in the template containing it
+  pub(crate) span: Span,
+  /// the token, with empty contents
+  pub(crate) token: Token<'static>,
+}
+
+impl Expansion {
+  /// Where each of the tokens returned by `tokens` came from.
+  #[must_use]
+  pub fn origins(&self) -> Vec<Origin> {
+    self
+      .tokens
+      .iter()
+      .map(|expanded| Origin {
+        path: expanded.path.map(|path| self.paths[path].as_str()),
+        span: expanded.span,
+      })
+      .collect()
+  }
+
+  /// The expanded template's tokens. As with `Token::parse`, text tokens
+  /// separated only by comments and escapes share an index.
+  #[must_use]
+  pub fn tokens(&self) -> Vec<Token> {
+    let mut index = 0;
+    let mut open = false;
+    let mut tokens = Vec::new();
+
+    for expanded in &self.tokens {
+      let token = expanded
+        .token
+        .with_contents(&self.src[expanded.contents.clone()]);
+
+      match token {
+        Token::Text { contents, .. } => {
+          tokens.push(Token::Text { contents, index });
+          open = true;
+        }
+        // escapes are appended to the preceding text, which may have been
+        // removed during expansion
+        Token::Escape { .. } => {
+          if !open {
+            tokens.push(Token::Text {
+              contents: "",
+              index,
+            });
+            open = true;
+          }
+          tokens.push(token);
+        }
+        Token::Comment { .. } | Token::CommentLine { .. } => tokens.push(token),
+        _ => {
+          tokens.push(token);
+          if open {
+            index += 1;
+            open = false;
+          }
+        }
+      }
+    }
+
+    tokens
+  }
+}
+
+/// The expanded template's source. Tokens from different templates may run
+/// together, so the source does not always parse to the same tokens.
+impl Display for Expansion {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    f.write_str(&self.src)
+  }
+}
@@ -1,7 +1,23 @@
-pub use self::{block::Block, error::Error, token::Token};
+pub use self::{
+  block::Block, delimiters::Delimiters, directive::Directive, error::Error, expand::expand,
+  expansion::Expansion, origin::Origin, span::Span, token::Token,
+};
 
-use core::fmt::{self, Display, Formatter};
+use {
+  self::{expansion::Expanded, span::Locator},
+  core::{
+    fmt::{self, Display, Formatter},
+    ops::Range,
+  },
+};
 
 mod block;
+mod delimiters;
+mod directive;
 mod error;
+mod expand;
+mod expansion;
+mod origin;
+mod rust;
+mod span;
 mod token;
@@ -0,0 +1,12 @@
+use super::*;
+
+/// Where a token in an expansion came from.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Origin<'a> {
+  /// Path of the included or extended template containing the token, as
+  /// given in the directive, or `None` if the token is in the template being
+  /// expanded.
+  pub path: Option<&'a str>,
+  /// Location of the token in the template containing it.
+  pub span: Span,
+}
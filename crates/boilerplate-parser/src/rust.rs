@@ -0,0 +1,119 @@
+/// Find the first occurrence of `pattern` in `src` which is not inside of a
+/// Rust string literal, character literal, or comment, and, if `balance` is
+/// true, is not inside of braces.
+pub(crate) fn find(src: &str, pattern: &str, balance: bool) -> Option<usize> {
+  let mut depth = 0usize;
+  let mut i = 0;
+
+  while i < src.len() {
+    let rest = &src[i..];
+
+    if depth == 0 && rest.starts_with(pattern) {
+      return Some(i);
+    }
+
+    let c = rest.chars().next().unwrap();
+
+    i += match c {
+      '"' => string(rest),
+      '\'' => character(rest),
+      '/' if rest.starts_with("//") => rest.find('\n').unwrap_or(rest.len()),
+      '/' if rest.starts_with("/*") => block_comment(rest),
+      'b' | 'c' | 'r' if !src[..i].ends_with(is_identifier) => raw_string(rest).unwrap_or(1),
+      '{' if balance => {
+        depth += 1;
+        1
+      }
+      '}' if depth > 0 => {
+        depth -= 1;
+        1
+      }
+      _ => c.len_utf8(),
+    };
+  }
+
+  None
+}
+
+fn is_identifier(c: char) -> bool {
+  c.is_alphanumeric() || c == '_'
+}
+
+/// Length of the block comment at the start of `rest`, which may be nested.
+fn block_comment(rest: &str) -> usize {
+  let mut depth = 0usize;
+  let mut i = 0;
+
+  while i < rest.len() {
+    if rest[i..].starts_with("/*") {
+      depth += 1;
+      i += 2;
+    } else if rest[i..].starts_with("*/") {
+      depth -= 1;
+      i += 2;
+      if depth == 0 {
+        return i;
+      }
+    } else {
+      i += rest[i..].chars().next().unwrap().len_utf8();
+    }
+  }
+
+  rest.len()
+}
+
+/// Length of the character literal at the start of `rest`, or one, if `rest`
+/// starts with a lifetime or label.
+fn character(rest: &str) -> usize {
+  let mut chars = rest[1..].chars();
+
+  match chars.next() {
+    Some('\\') => {
+      let escaped = 2 + chars.next().map_or(0, char::len_utf8);
+      rest[escaped..]
+        .find('\'')
+        .map_or(1, |close| escaped + close + 1)
+    }
+    Some(c) if chars.next() == Some('\'') => 1 + c.len_utf8() + 1,
+    _ => 1,
+  }
+}
+
+/// Length of the raw string literal at the start of `rest`, if any.
+fn raw_string(rest: &str) -> Option<usize> {
+  let prefixed = rest
+    .strip_prefix("br")
+    .or_else(|| rest.strip_prefix("cr"))
+    .or_else(|| rest.strip_prefix('r'))?;
+
+  let hashes = prefixed.len() - prefixed.trim_start_matches('#').len();
+
+  let contents = prefixed[hashes..].strip_prefix('"')?;
+
+  let terminator = format!("\"{}", "#".repeat(hashes));
+
+  let start = rest.len() - contents.len();
+
+  Some(
+    contents
+      .find(&terminator)
+      .map_or(rest.len(), |end| start + end + terminator.len()),
+  )
+}
+
+/// Length of the string literal at the start of `rest`.
+fn string(rest: &str) -> usize {
+  let mut chars = rest.char_indices().skip(1);
+
+  while let Some((i, c)) = chars.next() {
+    match c {
+      '"' => return i + 1,
+      '\\' => {
+        chars.next();
+      }
+      _ => {}
+    }
+  }
+
+  rest.len()
+}
@@ -0,0 +1,77 @@
+use super::*;
+
+/// Location of a token in template source.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Span {
+  /// Zero-based column of the first character, counted in characters.
+  pub column: usize,
+  /// Byte offset one past the last byte.
+  pub end: usize,
+  /// Zero-based line of the first character.
+  pub line: usize,
+  /// Byte offset of the first byte.
+  pub start: usize,
+}
+
+impl Display for Span {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    write!(f, "{}:{}", self.line + 1, self.column + 1)
+  }
+}
+
+impl Span {
+  /// Convert `self`, a span in text which starts at `start`, to a span in
+  /// the text containing it.
+  pub(crate) fn shift(self, start: Self) -> Self {
+    Self {
+      column: if self.line == 0 {
+        start.column + self.column
+      } else {
+        self.column
+      },
+      end: start.start + self.end,
+      line: start.line + self.line,
+      start: start.start + self.start,
+    }
+  }
+}
+
+/// Computes spans for monotonically increasing offsets into `src` without
+/// rescanning from the beginning.
+pub(crate) struct Locator<'src> {
+  column: usize,
+  line: usize,
+  offset: usize,
+  src: &'src str,
+}
+
+impl<'src> Locator<'src> {
+  pub(crate) fn new(src: &'src str) -> Self {
+    Self {
+      column: 0,
+      line: 0,
+      offset: 0,
+      src,
+    }
+  }
+
+  pub(crate) fn span(&mut self, start: usize, end: usize) -> Span {
+    for c in self.src[self.offset..start].chars() {
+      if c == '\n' {
+        self.line += 1;
+        self.column = 0;
+      } else {
+        self.column += 1;
+      }
+    }
+
+    self.offset = start;
+
+    Span {
+      column: self.column,
+      end,
+      line: self.line,
+      start,
+    }
+  }
+}
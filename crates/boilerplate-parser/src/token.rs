@@ -1,10 +1,16 @@
 use super::*;
 
 /// Parsed template token.
+///
+/// Text tokens separated only by comments and escapes share an index, and
+/// together with the escaped delimiters make up a single text block.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Token<'src> {
   Code { contents: &'src str },
   CodeLine { closed: bool, contents: &'src str },
+  Comment { contents: &'src str },
+  CommentLine { closed: bool, contents: &'src str },
+  Escape { contents: &'src str },
   Interpolation { contents: &'src str },
   InterpolationLine { closed: bool, contents: &'src str },
   Text { contents: &'src str, index: usize },
@@ -12,19 +18,39 @@ pub enum Token<'src> {
 
 impl Display for Token<'_> {
   fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-    let block = self.block();
+    self.display(&Delimiters::DEFAULT).fmt(f)
+  }
+}
+
+struct DisplayToken<'a, 'src> {
+  delimiters: &'a Delimiters<'a>,
+  token: Token<'src>,
+}
+
+impl Display for DisplayToken<'_, '_> {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    let token = self.token;
+
+    if let Token::Escape { contents } = token {
+      return write!(f, "{contents}{contents}");
+    }
+
+    let block = token.block();
 
     if let Some(block) = block {
-      write!(f, "{}", block.open_delimiter())?;
+      write!(f, "{}", block.open_delimiter(self.delimiters))?;
     }
 
-    write!(f, "{}", self.contents())?;
+    write!(f, "{}", token.contents())?;
 
-    match self {
-      Self::CodeLine { closed, .. } | Self::InterpolationLine { closed, .. } if !closed => {}
+    match token {
+      Token::CodeLine { closed, .. }
+      | Token::CommentLine { closed, .. }
+      | Token::InterpolationLine { closed, .. }
+        if !closed => {}
       _ => {
         if let Some(block) = block {
-          write!(f, "{}", block.close_delimiter())?;
+          write!(f, "{}", block.close_delimiter(self.delimiters))?;
         }
       }
     }
@@ -34,110 +60,263 @@ impl Display for Token<'_> {
 }
 
 impl<'src> Token<'src> {
-  pub fn parse(src: &'src str) -> Result<Vec<Self>, Error> {
-    let mut tokens = Vec::new();
+  pub fn parse(src: &'src str, delimiters: &Delimiters) -> Result<Vec<Self>, Error> {
+    Ok(
+      Self::parse_spanned(src, delimiters)?
+        .into_iter()
+        .map(|(token, _span)| token)
+        .collect(),
+    )
+  }
+
+  /// Parse `src`, returning each token along with its location in `src`.
+  pub fn parse_spanned(
+    src: &'src str,
+    delimiters: &Delimiters,
+  ) -> Result<Vec<(Self, Span)>, Error> {
+    let mut locator = Locator::new(src);
+    let mut tokens = Vec::<(Self, Span)>::new();
     let mut i = 0;
     let mut j = 0;
     let mut index = 0;
+    let mut open = false;
     while j < src.len() {
       let rest = &src[j..];
 
-      let Some(block) = Block::from_rest(rest) else {
+      let Some(block) = Block::from_rest(rest, delimiters) else {
         j += rest.chars().next().unwrap().len_utf8();
         continue;
       };
 
+      let open_delimiter = block.open_delimiter(delimiters);
+
+      // a doubled open delimiter is a literal open delimiter
+      if rest[open_delimiter.len()..].starts_with(open_delimiter) {
+        if i != j || !open {
+          tokens.push((
+            Self::Text {
+              contents: &src[i..j],
+              index,
+            },
+            locator.span(i, j),
+          ));
+          open = true;
+        }
+
+        let after_escape = j + open_delimiter.len() * 2;
+
+        tokens.push((
+          Self::Escape {
+            contents: &src[j..j + open_delimiter.len()],
+          },
+          locator.span(j, after_escape),
+        ));
+
+        j = after_escape;
+        i = j;
+        continue;
+      }
+
       let before_open = j;
-      let after_open = before_open + block.open_delimiter().len();
+      let after_open = before_open + block.open_delimiter(delimiters).len();
 
-      let (before_close, closed) = match src[after_open..].find(block.close_delimiter()) {
+      let (before_close, closed) = match block.find_close(&src[after_open..], delimiters) {
         Some(before_close) => (after_open + before_close, true),
         None if block.is_line() => (src.len(), false),
-        None => return Err(Error::Unclosed(block)),
+        None => {
+          return Err(Error::Unclosed {
+            block,
+            delimiter: block.open_delimiter(delimiters).into(),
+            span: locator.span(before_open, after_open),
+          });
+        }
       };
 
       let after_close = if closed {
-        before_close + block.close_delimiter().len()
+        before_close + block.close_delimiter(delimiters).len()
       } else {
         before_close
       };
 
-      let emit_empty = if cfg!(feature = "reload") {
-        let previous_is_code = matches!(
-          tokens.last(),
-          Some(Token::Code { .. } | Token::CodeLine { .. }),
-        );
+      if block.is_comment() {
+        if i != j {
+          tokens.push((
+            Self::Text {
+              contents: &src[i..j],
+              index,
+            },
+            locator.span(i, j),
+          ));
+          open = true;
+        }
+      } else {
+        let emit_empty = if cfg!(feature = "reload") {
+          let previous = Self::previous(&tokens);
+
+          let previous_is_code =
+            matches!(previous, Some(Token::Code { .. } | Token::CodeLine { .. }),);
+
+          let current_is_code = matches! {
+            block,
+            Block::Code | Block::CodeLine,
+          };
 
-        let current_is_code = matches! {
-          block,
-          Block::Code | Block::CodeLine,
+          previous.is_none() || !(previous_is_code && current_is_code)
+        } else {
+          false
         };
 
-        tokens.is_empty() || !(previous_is_code && current_is_code)
-      } else {
-        false
-      };
+        if i != j || (emit_empty && !open) {
+          tokens.push((
+            Self::Text {
+              contents: &src[i..j],
+              index,
+            },
+            locator.span(i, j),
+          ));
+          open = true;
+        }
 
-      if i != j || emit_empty {
-        tokens.push(Self::Text {
-          contents: &src[i..j],
-          index,
-        });
-        index += 1;
+        if open {
+          index += 1;
+          open = false;
+        }
       }
 
-      tokens.push(block.token(&src[after_open..before_close], closed));
+      tokens.push((
+        block.token(&src[after_open..before_close], closed),
+        locator.span(before_open, after_close),
+      ));
 
       j = after_close;
       i = after_close;
     }
 
     let emit_empty = if cfg!(feature = "reload") {
-      tokens.is_empty() || !matches!(tokens.last(), Some(Token::Text { .. }))
+      !matches!(Self::previous(&tokens), Some(Token::Text { .. }))
     } else {
       false
     };
 
-    if i != j || emit_empty {
-      tokens.push(Self::Text {
-        contents: &src[i..j],
-        index,
-      });
+    if i != j || (emit_empty && !open) {
+      tokens.push((
+        Self::Text {
+          contents: &src[i..j],
+          index,
+        },
+        locator.span(i, j),
+      ));
     }
 
     Ok(tokens)
   }
 
-  fn code(self) -> Option<&'src str> {
+  /// The last token which is not a comment or escape.
+  fn previous(tokens: &[(Self, Span)]) -> Option<Self> {
+    tokens
+      .iter()
+      .rev()
+      .map(|(token, _span)| *token)
+      .find(|token| !matches!(token, Self::Escape { .. }) && !token.is_comment())
+  }
+
+  /// The Rust code in `self`, without surrounding whitespace or trim markers.
+  #[must_use]
+  pub fn code(self) -> Option<&'src str> {
     match self {
       Self::Code { .. }
       | Self::CodeLine { .. }
       | Self::Interpolation { .. }
-      | Self::InterpolationLine { .. } => Some(self.contents().trim()),
-      Self::Text { .. } => None,
+      | Self::InterpolationLine { .. } => Some(self.markers().1.trim()),
+      Self::Comment { .. } | Self::CommentLine { .. } | Self::Escape { .. } | Self::Text { .. } => {
+        None
+      }
     }
   }
 
-  fn contents(self) -> &'src str {
+  pub(crate) fn contents(self) -> &'src str {
     match self {
       Self::Code { contents }
       | Self::CodeLine { contents, .. }
+      | Self::Comment { contents }
+      | Self::CommentLine { contents, .. }
+      | Self::Escape { contents }
       | Self::Interpolation { contents }
       | Self::InterpolationLine { contents, .. }
       | Self::Text { contents, .. } => contents,
     }
   }
 
-  fn block(self) -> Option<Block> {
+  /// `self` with `contents` in place of its contents.
+  pub(crate) fn with_contents(self, contents: &str) -> Token {
+    match self {
+      Self::Code { .. } => Token::Code { contents },
+      Self::CodeLine { closed, .. } => Token::CodeLine { closed, contents },
+      Self::Comment { .. } => Token::Comment { contents },
+      Self::CommentLine { closed, .. } => Token::CommentLine { closed, contents },
+      Self::Escape { .. } => Token::Escape { contents },
+      Self::Interpolation { .. } => Token::Interpolation { contents },
+      Self::InterpolationLine { closed, .. } => Token::InterpolationLine { closed, contents },
+      Self::Text { index, .. } => Token::Text { contents, index },
+    }
+  }
+
+  /// Split the contents of a block into its leading trim marker, the
+  /// remaining contents, and its trailing trim marker. Trim markers are `-`
+  /// characters at the beginning or end of a block, separated from the rest
+  /// of the block by whitespace, so that `{{-x}}` is still a negation.
+  fn markers(self) -> (bool, &'src str, bool) {
+    let contents = self.contents();
+
+    if !matches!(
+      self,
+      Self::Code { .. } | Self::Comment { .. } | Self::Interpolation { .. },
+    ) {
+      return (false, contents, false);
+    }
+
+    let (before, contents) = match contents.strip_prefix('-') {
+      Some(rest) if rest.is_empty() || rest.starts_with(char::is_whitespace) => (true, rest),
+      _ => (false, contents),
+    };
+
+    let (after, contents) = match contents.strip_suffix('-') {
+      Some(rest) if rest.ends_with(char::is_whitespace) => (true, rest),
+      _ => (false, contents),
+    };
+
+    (before, contents, after)
+  }
+
+  pub(crate) fn block(self) -> Option<Block> {
     match self {
       Self::Code { .. } => Some(Block::Code),
       Self::CodeLine { .. } => Some(Block::CodeLine),
+      Self::Comment { .. } => Some(Block::Comment),
+      Self::CommentLine { .. } => Some(Block::CommentLine),
       Self::Interpolation { .. } => Some(Block::Interpolation),
       Self::InterpolationLine { .. } => Some(Block::InterpolationLine),
-      Self::Text { .. } => None,
+      Self::Escape { .. } | Self::Text { .. } => None,
+    }
+  }
+
+  /// Display `self` using `delimiters`.
+  #[must_use]
+  pub fn display<'a>(self, delimiters: &'a Delimiters<'a>) -> impl Display + 'a
+  where
+    'src: 'a,
+  {
+    DisplayToken {
+      delimiters,
+      token: self,
     }
   }
 
+  #[must_use]
+  pub fn is_comment(self) -> bool {
+    self.block().is_some_and(Block::is_comment)
+  }
+
   #[must_use]
   pub fn is_compatible_with(self, other: Self) -> bool {
     if self.code() != other.code() {
@@ -162,6 +341,51 @@ impl<'src> Token<'src> {
     true
   }
 
+  /// Remove comments, escapes, and text tokens which continue a text block
+  /// after a comment or escape, so that templates which differ only in the
+  /// contents of their text blocks have the same tokens.
+  #[must_use]
+  pub fn coalesce(tokens: &[Self]) -> Vec<Self> {
+    let mut stripped = Vec::<Self>::new();
+
+    for token in tokens {
+      match token {
+        Self::Comment { .. } | Self::CommentLine { .. } | Self::Escape { .. } => {}
+        Self::Text { index, .. }
+          if matches!(
+            stripped.last(),
+            Some(Self::Text { index: previous, .. }) if previous == index,
+          ) => {}
+        _ => stripped.push(*token),
+      }
+    }
+
+    stripped
+  }
+
+  /// The directive in `self`, if `self` is a code block or code line
+  /// containing a directive.
+  #[must_use]
+  pub fn directive(self) -> Option<Directive<'src>> {
+    if !matches!(self, Self::Code { .. } | Self::CodeLine { .. }) {
+      return None;
+    }
+
+    Directive::parse(self.code()?)
+  }
+
+  /// Whether `self` trims whitespace from the start of the following text.
+  #[must_use]
+  pub fn trim_after(self) -> bool {
+    self.markers().2
+  }
+
+  /// Whether `self` trims whitespace from the end of the preceding text.
+  #[must_use]
+  pub fn trim_before(self) -> bool {
+    self.markers().0
+  }
+
   #[must_use]
   pub fn text(self) -> Option<&'src str> {
     if let Self::Text { contents, .. } = self {
@@ -170,6 +394,40 @@ impl<'src> Token<'src> {
       None
     }
   }
+
+  /// Collect the text blocks of `tokens`, concatenating text tokens which share
+  /// an index, along with the delimiters of escapes between them, and trimming
+  /// whitespace next to blocks with trim markers.
+  #[must_use]
+  pub fn text_blocks(tokens: &[Self]) -> Vec<String> {
+    let mut blocks = Vec::<String>::new();
+
+    for (i, token) in tokens.iter().enumerate() {
+      match *token {
+        Self::Escape { contents } => blocks.last_mut().unwrap().push_str(contents),
+        Self::Text {
+          mut contents,
+          index,
+        } => {
+          if i > 0 && tokens[i - 1].trim_after() {
+            contents = contents.trim_start();
+          }
+
+          if tokens.get(i + 1).is_some_and(|next| next.trim_before()) {
+            contents = contents.trim_end();
+          }
+
+          if index == blocks.len() {
+            blocks.push(String::new());
+          }
+          blocks[index].push_str(contents);
+        }
+        _ => {}
+      }
+    }
+
+    blocks
+  }
 }
 
 #[cfg(test)]
@@ -253,6 +511,16 @@ mod tests {
       Interpolation { contents: "foo" },
       Interpolation { contents: "foo" },
     );
+    case(
+      Interpolation { contents: "- foo" },
+      Interpolation { contents: "foo -" },
+    );
+    case(
+      Code {
+        contents: "- foo -",
+      },
+      Code { contents: "foo" },
+    );
     case(
       InterpolationLine {
         contents: "foo",
@@ -289,6 +557,10 @@ mod tests {
       Code { contents: "bar" },
     );
     case(Code { contents: "foo" }, Interpolation { contents: "bar" });
+    case(
+      Interpolation { contents: "-foo" },
+      Interpolation { contents: "foo" },
+    );
     case(
       Interpolation { contents: "foo" },
       InterpolationLine {
@@ -320,7 +592,7 @@ mod tests {
 
   #[track_caller]
   fn assert_parse(expected: &str, expected_tokens: &[Token]) {
-    let actual_tokens = Token::parse(expected).unwrap();
+    let actual_tokens = Token::parse(expected, &Delimiters::DEFAULT).unwrap();
     assert_eq!(actual_tokens, expected_tokens);
     let actual = actual_tokens
       .iter()
@@ -618,6 +890,286 @@ mod tests {
     );
   }
 
+  #[test]
+  fn comment() {
+    assert_parse(
+      "{{# foo #}}",
+      &[
+        Comment { contents: " foo " },
+        #[cfg(feature = "reload")]
+        Text {
+          contents: "",
+          index: 0,
+        },
+      ],
+    );
+    assert_parse(
+      "foo {{# bar #}} baz",
+      &[
+        Text {
+          contents: "foo ",
+          index: 0,
+        },
+        Comment { contents: " bar " },
+        Text {
+          contents: " baz",
+          index: 0,
+        },
+      ],
+    );
+    assert_parse(
+      "foo {{# bar #}}{{ baz }} bob",
+      &[
+        Text {
+          contents: "foo ",
+          index: 0,
+        },
+        Comment { contents: " bar " },
+        Interpolation { contents: " baz " },
+        Text {
+          contents: " bob",
+          index: 1,
+        },
+      ],
+    );
+    assert_parse(
+      "{% foo %}{{# bar #}}{% baz %}",
+      &[
+        #[cfg(feature = "reload")]
+        Text {
+          contents: "",
+          index: 0,
+        },
+        Code { contents: " foo " },
+        Comment { contents: " bar " },
+        Code { contents: " baz " },
+        #[cfg(feature = "reload")]
+        Text {
+          contents: "",
+          index: 1,
+        },
+      ],
+    );
+  }
+
+  #[test]
+  fn comment_line() {
+    assert_parse(
+      "$$# foo\nbar",
+      &[
+        CommentLine {
+          contents: " foo",
+          closed: true,
+        },
+        Text {
+          contents: "bar",
+          index: 0,
+        },
+      ],
+    );
+    assert_parse(
+      "foo\n$$# bar",
+      &[
+        Text {
+          contents: "foo\n",
+          index: 0,
+        },
+        CommentLine {
+          contents: " bar",
+          closed: false,
+        },
+      ],
+    );
+    assert_parse(
+      "$$#",
+      &[
+        CommentLine {
+          contents: "",
+          closed: false,
+        },
+        #[cfg(feature = "reload")]
+        Text {
+          contents: "",
+          index: 0,
+        },
+      ],
+    );
+  }
+
+  #[test]
+  fn escape() {
+    assert_parse(
+      "{{{{",
+      &[
+        Text {
+          contents: "",
+          index: 0,
+        },
+        Escape { contents: "{{" },
+      ],
+    );
+    assert_parse(
+      "foo {{{{ bar }} %%%% baz",
+      &[
+        Text {
+          contents: "foo ",
+          index: 0,
+        },
+        Escape { contents: "{{" },
+        Text {
+          contents: " bar }} ",
+          index: 0,
+        },
+        Escape { contents: "%%" },
+        Text {
+          contents: " baz",
+          index: 0,
+        },
+      ],
+    );
+    assert_parse(
+      "{%{%{{#{{#$$$$$$#$$#",
+      &[
+        Text {
+          contents: "",
+          index: 0,
+        },
+        Escape { contents: "{%" },
+        Escape { contents: "{{#" },
+        Escape { contents: "$$" },
+        Escape { contents: "$$#" },
+      ],
+    );
+    assert_parse(
+      "{{{{{{ foo }}",
+      &[
+        Text {
+          contents: "",
+          index: 0,
+        },
+        Escape { contents: "{{" },
+        Interpolation { contents: " foo " },
+        #[cfg(feature = "reload")]
+        Text {
+          contents: "",
+          index: 1,
+        },
+      ],
+    );
+    assert_parse(
+      "{{{{#",
+      &[
+        Text {
+          contents: "",
+          index: 0,
+        },
+        Escape { contents: "{{" },
+        Text {
+          contents: "#",
+          index: 0,
+        },
+      ],
+    );
+    assert_parse(
+      "\\{{ foo }}",
+      &[
+        Text {
+          contents: "\\",
+          index: 0,
+        },
+        Interpolation { contents: " foo " },
+        #[cfg(feature = "reload")]
+        Text {
+          contents: "",
+          index: 1,
+        },
+      ],
+    );
+    assert_parse(
+      "\\ \\{ {} %$ foo\\",
+      &[Text {
+        contents: "\\ \\{ {} %$ foo\\",
+        index: 0,
+      }],
+    );
+  }
+
+  #[test]
+  fn text_blocks() {
+    #[track_caller]
+    fn case(src: &str, expected: &[&str]) {
+      let actual = Token::text_blocks(&Token::parse(src, &Delimiters::DEFAULT).unwrap());
+      assert_eq!(actual, expected);
+    }
+
+    case("foo", &["foo"]);
+    case("foo {{# bar #}} baz", &["foo  baz"]);
+    case("foo\n$$# bar\nbaz {{ bob }} bill", &["foo\nbaz ", " bill"]);
+    case("{{# foo #}}{{# bar #}}baz", &["baz"]);
+    case("{{{{ foo }}", &["{{ foo }}"]);
+    case("foo $$$$ {{# bar #}}%%%% baz", &["foo $$ %% baz"]);
+    case("foo \n {{- bar -}} \n baz", &["foo", "baz"]);
+    case("foo \n {%- bar %} \n baz", &["foo", " \n baz"]);
+    case("foo \n {% bar -%} \n baz", &["foo \n ", "baz"]);
+    case("foo \n {{#- bar -#}} \n baz", &["foobaz"]);
+    case("foo {{-bar}} baz", &["foo ", " baz"]);
+    case("foo {{ bar-}} baz", &["foo ", " baz"]);
+    case("foo\n%% bar -%}\n baz", &["foo\n", " baz"]);
+    case("{{{{ {{- foo -}} {{{{ ", &["{{", "{{ "]);
+    case("C:\\{{ foo }}\\\\", &["C:\\", "\\\\"]);
+  }
+
+  #[test]
+  fn trim_markers() {
+    #[track_caller]
+    fn case(src: &str, before: bool, code: &str, after: bool) {
+      let tokens = Token::parse(src, &Delimiters::DEFAULT).unwrap();
+      let token = tokens
+        .into_iter()
+        .find(|token| token.text().is_none())
+        .unwrap();
+      assert_eq!(token.trim_before(), before);
+      assert_eq!(token.code().unwrap_or_default(), code);
+      assert_eq!(token.trim_after(), after);
+    }
+
+    case("{{- foo -}}", true, "foo", true);
+    case("{{- foo }}", true, "foo", false);
+    case("{{ foo -}}", false, "foo", true);
+    case("{{-foo}}", false, "-foo", false);
+    case("{{ foo- }}", false, "foo-", false);
+    case("{%-\tfoo\n-%}", true, "foo", true);
+    case("{%-%}", true, "", false);
+    case("{%- -%}", true, "", true);
+    case("{{#- foo -#}}", true, "", true);
+    case("%%- foo -\n", false, "- foo -", false);
+    case("$$- foo\n", false, "- foo", false);
+  }
+
+  #[test]
+  fn comments_and_escapes_do_not_change_structure() {
+    #[track_caller]
+    fn case(a: &str, b: &str) {
+      let a = Token::coalesce(&Token::parse(a, &Delimiters::DEFAULT).unwrap());
+      let b = Token::coalesce(&Token::parse(b, &Delimiters::DEFAULT).unwrap());
+      assert_eq!(a.len(), b.len());
+      for (a, b) in a.into_iter().zip(b) {
+        assert!(a.is_compatible_with(b));
+      }
+    }
+
+    case("foo {{# bar #}} baz", "foo baz");
+    case("{{# foo #}}", "");
+    case("$$# foo\n", "");
+    case("{% foo %}{{# bar #}}{% baz %}", "{% foo %}{% baz %}");
+    case("{% foo %} x {{# bar #}}{% baz %}", "{% foo %} x {% baz %}");
+    case("{{ foo }}{{# bar #}}", "{{ foo }}");
+    case("{{# bar #}}{{ foo }}", "{{ foo }}");
+    case("foo {{{{ bar", "foo bar");
+    case("{{{{ a {{ b }}", "c {{ b }}");
+    case("{{ a }} $$$$", "{{ a }} b");
+  }
+
   #[test]
   fn mixed() {
     assert_parse(
@@ -842,12 +1394,90 @@ mod tests {
         index: 0,
       }],
     );
+    assert_parse(
+      "printf '%#x' ${#arr[@]} {# #} $#",
+      &[Text {
+        contents: "printf '%#x' ${#arr[@]} {# #} $#",
+        index: 0,
+      }],
+    );
+  }
+
+  #[test]
+  fn custom_delimiters() {
+    const DELIMITERS: Delimiters = Delimiters {
+      code: ("<%", "%>"),
+      code_line: "%",
+      comment: ("<%#", "%>"),
+      comment_line: "",
+      interpolation: ("<%=", "%>"),
+      interpolation_line: "",
+    };
+
+    #[track_caller]
+    fn case(src: &str, expected: &[Token]) {
+      let actual = Token::parse(src, &DELIMITERS).unwrap();
+      assert_eq!(actual, expected);
+      let round_trip = actual
+        .iter()
+        .map(|token| token.display(&DELIMITERS).to_string())
+        .collect::<String>();
+      assert_eq!(round_trip, src);
+    }
+
+    case(
+      "{{ a }} <%= b %> $$ <%# c %><%<% d",
+      &[
+        Text {
+          contents: "{{ a }} ",
+          index: 0,
+        },
+        Interpolation { contents: " b " },
+        Text {
+          contents: " $$ ",
+          index: 1,
+        },
+        Comment { contents: " c " },
+        Escape { contents: "<%" },
+        Text {
+          contents: " d",
+          index: 1,
+        },
+      ],
+    );
+
+    case(
+      "a\n% if b {\n<% } %>",
+      &[
+        Text {
+          contents: "a\n",
+          index: 0,
+        },
+        CodeLine {
+          contents: " if b {",
+          closed: true,
+        },
+        Code { contents: " } " },
+        #[cfg(feature = "reload")]
+        Text {
+          contents: "",
+          index: 1,
+        },
+      ],
+    );
+
+    assert_eq!(
+      Token::parse("a <%= b", &DELIMITERS)
+        .unwrap_err()
+        .to_string(),
+      "unmatched `<%=` at line 1, column 3",
+    );
   }
 
   #[test]
   fn nesting() {
     assert_parse(
-      "{{ foo {{ bar }}",
+      "{{ foo {{ bar }} }}",
       &[
         #[cfg(feature = "reload")]
         Text {
@@ -855,7 +1485,7 @@ mod tests {
           index: 0,
         },
         Interpolation {
-          contents: " foo {{ bar ",
+          contents: " foo {{ bar }} ",
         },
         #[cfg(feature = "reload")]
         Text {
@@ -884,6 +1514,49 @@ mod tests {
     );
   }
 
+  #[test]
+  fn rust() {
+    #[track_caller]
+    fn case(src: &str, contents: &str) {
+      let tokens = Token::parse(src, &Delimiters::DEFAULT).unwrap();
+      let token = tokens.iter().find(|token| token.text().is_none()).unwrap();
+      assert_eq!(token.contents(), contents);
+      assert_eq!(
+        tokens.iter().map(ToString::to_string).collect::<String>(),
+        src,
+      );
+    }
+
+    case("{{ format!(\"}}\") }}", " format!(\"}}\") ");
+    case("{{ \"\\\"}}\" }}", " \"\\\"}}\" ");
+    case("{{ b\"}}\" }}", " b\"}}\" ");
+    case("{{ r\"}}\" }}", " r\"}}\" ");
+    case("{{ r#\"\"}}\"# }}", " r#\"\"}}\"# ");
+    case("{{ br##\"\"#}}\"## }}", " br##\"\"#}}\"## ");
+    case("{{ cr\"}}\" }}", " cr\"}}\" ");
+    case("{{ '}' }}", " '}' ");
+    case("{{ '\\'' }} }}", " '\\'' ");
+    case("{{ '\\u{7d}' }}", " '\\u{7d}' ");
+    case("{{ x as &'static str }}", " x as &'static str ");
+    case(
+      "{{ 'a: loop { break 'a 1; } }}",
+      " 'a: loop { break 'a 1; } ",
+    );
+    case("{{ x /* }} */ }}", " x /* }} */ ");
+    case("{{ x /* /* */ }} */ }}", " x /* /* */ }} */ ");
+    case("{{ x // }}\n }}", " x // }}\n ");
+    case("{{ Foo { a: Bar {} }}}", " Foo { a: Bar {} }");
+    case("{{ Foo { a: 1 } }}", " Foo { a: 1 } ");
+    case("{{ ar\"\\\"}}\" }}", " ar\"\\\"}}\" ");
+    case("{{ for r in x {} }}", " for r in x {} ");
+    case("{% let s = \"%}\"; %}", " let s = \"%}\"; ");
+    case("{% if x { %}", " if x { ");
+    case("{% } %}", " } ");
+    case("{% x('%') %}", " x('%') ");
+    case("{{# \"#}}", " \"");
+    case("%% let s = \"\n", " let s = \"");
+  }
+
   #[test]
   fn unicode() {
     assert_parse(
@@ -1058,10 +1731,61 @@ Done.",
 
   #[test]
   fn unclosed() {
-    assert_eq!(Token::parse("{%"), Err(Error::Unclosed(Block::Code)),);
     assert_eq!(
-      Token::parse("{{"),
-      Err(Error::Unclosed(Block::Interpolation)),
+      Token::parse("{%", &Delimiters::DEFAULT),
+      Err(Error::Unclosed {
+        block: Block::Code,
+        delimiter: "{%".into(),
+        span: Span {
+          column: 0,
+          end: 2,
+          line: 0,
+          start: 0,
+        },
+      }),
+    );
+    assert_eq!(
+      Token::parse("{{", &Delimiters::DEFAULT),
+      Err(Error::Unclosed {
+        block: Block::Interpolation,
+        delimiter: "{{".into(),
+        span: Span {
+          column: 0,
+          end: 2,
+          line: 0,
+          start: 0,
+        },
+      }),
+    );
+    assert_eq!(
+      Token::parse("foo\nbär {{ baz }}\n  {{ bob", &Delimiters::DEFAULT)
+        .unwrap_err()
+        .to_string(),
+      "unmatched `{{` at line 3, column 3",
+    );
+  }
+
+  #[test]
+  fn spans() {
+    #[track_caller]
+    fn case(src: &str, expected: &[(usize, usize, usize, usize)]) {
+      let actual = Token::parse_spanned(src, &Delimiters::DEFAULT)
+        .unwrap()
+        .into_iter()
+        .filter(|(token, _)| token.text() != Some(""))
+        .map(|(_, span)| (span.start, span.end, span.line, span.column))
+        .collect::<Vec<(usize, usize, usize, usize)>>();
+      assert_eq!(actual, expected);
+    }
+
+    case("foo", &[(0, 3, 0, 0)]);
+    case("{{ foo }}", &[(0, 9, 0, 0)]);
+    case("a\n{% b %}c", &[(0, 2, 0, 0), (2, 9, 1, 0), (9, 10, 1, 7)]);
+    case("é{{ x }}", &[(0, 2, 0, 0), (2, 9, 0, 1)]);
+    case(
+      "%% a\n  $$ b\nc",
+      &[(0, 5, 0, 0), (5, 7, 1, 0), (7, 12, 1, 2), (12, 13, 2, 0)],
     );
+    case("$$ a", &[(0, 4, 0, 0)]);
   }
 }
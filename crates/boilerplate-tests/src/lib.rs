@@ -25,6 +25,20 @@ fn foo() {
   boilerplate::boilerplate!("Hello!");
 }
 
+#[allow(unused)]
+fn display(w: &mut dyn core::fmt::Write, name: &str) -> core::fmt::Result {
+  write!(
+    w,
+    "{}",
+    boilerplate::boilerplate_display!("Hello, {{ name }}!")
+  )
+}
+
+#[allow(unused)]
+fn greet(name: &str) -> impl core::fmt::Display + '_ {
+  boilerplate::boilerplate_display!("Hello, {{ name }}!")
+}
+
 #[cfg(test)]
 mod tests {
   extern crate alloc;
@@ -77,6 +91,127 @@ mod tests {
     assert_eq!(ContextHtml(Foo).to_string(), "&amp;");
   }
 
+  #[test]
+  fn nested_html_templates_are_escaped_once() {
+    #[derive(boilerplate::Boilerplate)]
+    #[boilerplate(axum = false, text = "<p title=\"{{ self.0 }}\">{{ self.0 }}</p>")]
+    struct OuterHtml(InnerHtml);
+
+    #[derive(boilerplate::Boilerplate)]
+    #[boilerplate(axum = false, text = "<b>{{ self.0 }}</b>")]
+    struct InnerHtml(&'static str);
+
+    assert_eq!(
+      OuterHtml(InnerHtml("&")).to_string(),
+      "<p title=\"&lt;b&gt;&amp;amp;&lt;/b&gt;\"><b>&amp;</b></p>",
+    );
+  }
+
+  #[test]
+  fn generic_enum() {
+    use core::fmt::Display;
+
+    #[expect(clippy::enum_variant_names)]
+    #[derive(boilerplate::Boilerplate)]
+    #[boilerplate(axum = false)]
+    enum Page<'a, T: Display, const N: usize> {
+      #[boilerplate(text = "<p>{{ self.0 }}</p>")]
+      TupleHtml(&'a T),
+      #[boilerplate(text = "<p>{% for x in self.items { %}{{ x }}{% } %}</p>")]
+      StructHtml { items: [T; N] },
+      #[boilerplate(text = "<p>{{ N }}</p>")]
+      UnitHtml,
+    }
+
+    #[derive(boilerplate::Boilerplate)]
+    #[boilerplate(axum = false, text = "<div>{{ self.0 }}</div>")]
+    struct OuterHtml(Page<'static, &'static str, 2>);
+
+    #[track_caller]
+    fn case(page: Page<&str, 2>, expected: &str) {
+      assert_eq!(page.to_string(), expected);
+      assert_eq!(boilerplate::Boilerplate::render(&page).unwrap(), expected);
+    }
+
+    case(Page::TupleHtml(&"&"), "<p>&amp;</p>");
+    case(Page::StructHtml { items: ["<", ">"] }, "<p>&lt;&gt;</p>");
+    case(Page::UnitHtml, "<p>2</p>");
+
+    assert_eq!(OuterHtml(Page::UnitHtml).to_string(), "<div><p>2</p></div>");
+  }
+
+  #[test]
+  fn fallible_enum() {
+    use core::fmt::{self, Write};
+
+    #[derive(Debug, PartialEq)]
+    struct Error;
+
+    impl From<fmt::Error> for Error {
+      fn from(_: fmt::Error) -> Self {
+        Self
+      }
+    }
+
+    fn lookup(n: u8) -> Result<u8, Error> {
+      n.checked_sub(1).ok_or(Error)
+    }
+
+    #[derive(boilerplate::Boilerplate)]
+    #[boilerplate(axum = false, error = Error)]
+    enum Fallible {
+      #[boilerplate(text = "{{ lookup(*self.0)? }}")]
+      Lookup(u8),
+      #[boilerplate(text = "ok")]
+      Unit,
+    }
+
+    assert_eq!(Fallible::Lookup(2).try_render().unwrap(), "1");
+    assert_eq!(Fallible::Lookup(0).try_render(), Err(Error));
+    assert_eq!(Fallible::Unit.try_render().unwrap(), "ok");
+
+    assert!(write!(alloc::string::String::new(), "{}", Fallible::Lookup(0)).is_err());
+  }
+
+  #[test]
+  fn display() {
+    let mut output = alloc::string::String::new();
+    super::display(&mut output, "Bob").unwrap();
+    assert_eq!(output, "Hello, Bob!");
+  }
+
+  #[test]
+  fn display_returned_from_function() {
+    let name = alloc::string::String::from("Bob");
+    assert_eq!(super::greet(&name).to_string(), "Hello, Bob!");
+  }
+
+  #[test]
+  fn manual_implementation() {
+    struct Greeting;
+
+    impl boilerplate::Boilerplate for Greeting {
+      const TEMPLATE: &'static str = "Hello, {{ \"world\" }}!";
+
+      const TEXT: &'static [&'static str] = &["Hello, ", "!"];
+
+      fn boilerplate(
+        &self,
+        boilerplate_text: &[impl AsRef<str>],
+        boilerplate_output: &mut dyn core::fmt::Write,
+      ) -> core::fmt::Result {
+        boilerplate_output.write_str(boilerplate_text[0].as_ref())?;
+        boilerplate_output.write_str("world")?;
+        boilerplate_output.write_str(boilerplate_text[1].as_ref())
+      }
+    }
+
+    assert_eq!(
+      boilerplate::Boilerplate::render(&Greeting).unwrap(),
+      "Hello, world!"
+    );
+  }
+
   #[test]
   fn block_and_line_are_equivalent() {
     #[track_caller]
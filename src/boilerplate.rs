@@ -9,20 +9,75 @@ pub trait Boilerplate {
   /// The parsed template's text blocks.
   const TEXT: &'static [&'static str];
 
+  /// An estimate of the length of the rendered template, used to preallocate
+  /// output. The sum of the lengths of the template's text blocks, plus an
+  /// estimate for each interpolation, which defaults to 16 and can be set
+  /// with `#[boilerplate(interpolation_size_hint = N)]`.
+  const SIZE_HINT: usize = 0;
+
+  #[cfg(feature = "reload")]
+  /// The delimiters used to parse the template.
+  const DELIMITERS: Delimiters<'static> = Delimiters::DEFAULT;
+
+  #[cfg(feature = "reload")]
+  /// The names of templates included or extended by the original template,
+  /// as given in their directives, and their contents.
+  const INCLUDES: &'static [(&'static str, &'static str)] = &[];
+
   #[cfg(feature = "reload")]
   /// Path to the original template file.
-  const PATH: Option<&'static str>;
+  const PATH: Option<&'static str> = None;
+
+  #[cfg(feature = "reload")]
+  /// Path to the directory that included templates are read from.
+  const ROOT: &'static str = "templates";
 
   /// Render the template.
   ///
   /// - `boilerplate_text` - The template's text blocks.
-  /// - `boilerplate_output` - The formatter to write to.
+  /// - `boilerplate_output` - The writer to write to.
   fn boilerplate(
     &self,
     boilerplate_text: &[impl AsRef<str>],
-    boilerplate_output: &mut fmt::Formatter,
+    boilerplate_output: &mut dyn Write,
   ) -> fmt::Result;
 
+  /// Render the template to a new `String`, preallocated with
+  /// `Self::SIZE_HINT`.
+  fn render(&self) -> Result<String, fmt::Error> {
+    let mut output = String::with_capacity(Self::SIZE_HINT);
+    self.boilerplate(Self::TEXT, &mut output)?;
+    Ok(output)
+  }
+
+  /// Render the template, appending the output to `output`, which is first
+  /// grown by `Self::SIZE_HINT`.
+  fn render_into(&self, output: &mut String) -> fmt::Result {
+    output.reserve(Self::SIZE_HINT);
+    self.boilerplate(Self::TEXT, output)
+  }
+
+  #[cfg(feature = "std")]
+  /// Render the template to `writer`, without first rendering it to a
+  /// `String`.
+  ///
+  /// The template is written in many small writes, so `writer` should usually
+  /// be buffered, for example by wrapping it in a `std::io::BufWriter`.
+  fn render_to_io(&self, writer: impl io::Write) -> io::Result<()> {
+    let mut writer = IoWriter {
+      error: None,
+      inner: writer,
+    };
+
+    self
+      .boilerplate(Self::TEXT, &mut writer)
+      .map_err(|fmt::Error| {
+        writer
+          .error
+          .unwrap_or_else(|| io::Error::other("formatter error"))
+      })
+  }
+
   #[cfg(feature = "reload")]
   /// Reload the template from a new template string.
   ///
@@ -31,10 +86,31 @@ pub trait Boilerplate {
   /// Rust code, like `{{ ... }}` are the same. Text blocks, i.e., blocks that
   /// contain literal text, may be different.
   ///
+  /// The new template is parsed with the same delimiters as the original
+  /// template, and included templates are read from disk.
+  ///
   /// - `src` - The new template source text.
   fn reload(&self, src: &str) -> Result<Reload<&Self>, Error> {
-    let new = Token::parse(src).map_err(Error::ParseNew)?;
-    let old = Token::parse(Self::TEMPLATE).map_err(Error::ParseOld)?;
+    let new = boilerplate_parser::expand(src, &Self::DELIMITERS, &mut |path| {
+      std::fs::read_to_string(std::path::Path::new(Self::ROOT).join(path)).ok()
+    })
+    .map_err(Error::ParseNew)?;
+
+    let old = boilerplate_parser::expand(Self::TEMPLATE, &Self::DELIMITERS, &mut |path| {
+      Self::INCLUDES
+        .iter()
+        .find(|(name, _src)| *name == path)
+        .map(|(_name, src)| (*src).into())
+    })
+    .map_err(Error::ParseOld)?;
+
+    let new = new.tokens();
+    let old = old.tokens();
+
+    let text = Token::text_blocks(&new);
+
+    let new = Token::coalesce(&new);
+    let old = Token::coalesce(&old);
 
     if new.len() != old.len() {
       return Err(Error::Length {
@@ -46,20 +122,13 @@ pub trait Boilerplate {
     for (new, old) in new.iter().zip(old) {
       if !new.is_compatible_with(old) {
         return Err(Error::Incompatible {
-          new: new.to_string(),
-          old: old.to_string(),
+          new: new.display(&Self::DELIMITERS).to_string(),
+          old: old.display(&Self::DELIMITERS).to_string(),
         });
       }
     }
 
-    Ok(Reload {
-      inner: self,
-      text: new
-        .into_iter()
-        .filter_map(Token::text)
-        .map(ToOwned::to_owned)
-        .collect(),
-    })
+    Ok(Reload { inner: self, text })
   }
 
   #[cfg(feature = "reload")]
@@ -0,0 +1,23 @@
+use super::*;
+
+/// A template returned by `boilerplate_display!`, which is rendered by a
+/// closure each time it is displayed. Templates with context-aware HTML
+/// escaping set `HTML_SAFE`, and implement `HtmlSafe`.
+#[doc(hidden)]
+pub struct DisplayFn<F, const HTML_SAFE: bool>(F);
+
+impl<F: Fn(&mut fmt::Formatter) -> fmt::Result, const HTML_SAFE: bool> DisplayFn<F, HTML_SAFE> {
+  pub fn new(f: F) -> Self {
+    Self(f)
+  }
+}
+
+impl<F: Fn(&mut fmt::Formatter) -> fmt::Result, const HTML_SAFE: bool> Display
+  for DisplayFn<F, HTML_SAFE>
+{
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    (self.0)(f)
+  }
+}
+
+impl<F: Fn(&mut fmt::Formatter) -> fmt::Result> HtmlSafe for DisplayFn<F, true> {}
@@ -0,0 +1,31 @@
+use super::*;
+
+/// Escape the wrapped value with an escaper other than the template's.
+///
+/// ```
+/// use boilerplate::{Escaped, escaper::UrlComponent};
+///
+/// #[derive(boilerplate::Boilerplate)]
+/// #[boilerplate(text = "<a href=\"/search?q={{ Escaped(UrlComponent, self.0) }}\">")]
+/// struct SearchHtml(&'static str);
+///
+/// assert_eq!(
+///   SearchHtml("cats & dogs").to_string(),
+///   "<a href=\"/search?q=cats%20%26%20dogs\">",
+/// );
+/// ```
+pub struct Escaped<E: Escaper, T: Display>(pub E, pub T);
+
+impl<E: Escaper, T: Display> Format for Escaped<E, T> {
+  fn format(
+    &self,
+    f: &mut dyn Write,
+    _escaper: &dyn Escaper,
+    indent: &str,
+    trim: bool,
+  ) -> fmt::Result {
+    self
+      .0
+      .format(&mut Formatter::new(f).indent(indent).trim(trim), &self.1)
+  }
+}
@@ -0,0 +1,71 @@
+//! Escapers for interpolated values.
+//!
+//! Templates with an `html` or `htm` extension use [`Html`] for element
+//! content and attribute values, [`Url`] for URL attribute values, and [`Css`]
+//! and [`Js`] for string literals in `<style>` and `<script>` elements.
+//!
+//! Templates with other extensions use a single escaper for all
+//! interpolations:
+//!
+//! | extension | escaper  |
+//! |-----------|----------|
+//! | `css`     | [`Css`]  |
+//! | `js`      | [`Js`]   |
+//! | `json`    | [`Json`] |
+//! | `sh`      | [`Sh`]   |
+//! | `tex`     | [`Tex`]  |
+//! | `xml`     | [`Html`] |
+//!
+//! [`UrlComponent`] is not selected by any extension, but may be used with
+//! `#[boilerplate(escape = ...)]` or `boilerplate::Escaped`.
+
+use super::*;
+
+pub use self::{
+  css::Css, html::Html, js::Js, json::Json, sh::Sh, tex::Tex, url::Url, url_component::UrlComponent,
+};
+
+mod css;
+mod html;
+mod js;
+mod json;
+mod sh;
+mod tex;
+mod url;
+mod url_component;
+
+/// Escapes interpolated values.
+///
+/// Use a custom escaper with `#[boilerplate(escape = path::to::Escaper)]`,
+/// where the path is a value implementing `Escaper`, usually a unit struct.
+pub trait Escaper {
+  /// Write `s` to `f`, escaped.
+  fn escape(&self, f: &mut dyn Write, s: &str) -> fmt::Result;
+
+  /// Write `value` to `f`, escaped.
+  ///
+  /// By default, `value` is formatted in pieces, each of which is escaped
+  /// with `escape`. Escapers which must see the whole value at once may
+  /// override this.
+  fn format(&self, f: &mut dyn Write, value: &dyn Display) -> fmt::Result {
+    write!(
+      Escape {
+        escaper: self,
+        inner: f,
+      },
+      "{value}"
+    )
+  }
+}
+
+/// A `core::fmt::Write` adapter which escapes its input with `escaper`.
+struct Escape<'a, E: ?Sized> {
+  escaper: &'a E,
+  inner: &'a mut dyn Write,
+}
+
+impl<E: Escaper + ?Sized> Write for Escape<'_, E> {
+  fn write_str(&mut self, s: &str) -> fmt::Result {
+    self.escaper.escape(self.inner, s)
+  }
+}
@@ -0,0 +1,37 @@
+use super::*;
+
+/// Escapes values for inclusion in quoted CSS strings, using hexadecimal
+/// escapes.
+pub struct Css;
+
+impl Escaper for Css {
+  fn escape(&self, f: &mut dyn Write, s: &str) -> fmt::Result {
+    let mut start = 0;
+
+    for (i, c) in s.char_indices() {
+      if c.is_ascii_alphanumeric() || matches!(c, ' ' | ',' | '-' | '.' | '_') || !c.is_ascii() {
+        continue;
+      }
+
+      f.write_str(&s[start..i])?;
+      write!(f, "\\{:x} ", u32::from(c))?;
+      start = i + c.len_utf8();
+    }
+
+    f.write_str(&s[start..])
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, alloc::string::String};
+
+  #[test]
+  fn escape() {
+    let mut output = String::new();
+    Css
+      .escape(&mut output, "a-b_c. d\"e'f\\g</style>é")
+      .unwrap();
+    assert_eq!(output, "a-b_c. d\\22 e\\27 f\\5c g\\3c \\2f style\\3e é");
+  }
+}
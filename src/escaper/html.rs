@@ -0,0 +1,183 @@
+use super::*;
+
+/// Escapes the characters which are special in HTML and XML, `"`, `&`, `'`,
+/// `<`, and `>`, as character references.
+pub struct Html;
+
+impl Escaper for Html {
+  fn escape(&self, f: &mut dyn Write, s: &str) -> fmt::Result {
+    let bytes = s.as_bytes();
+    let mut chunk_start = 0;
+
+    while let Some(i) = find(&bytes[chunk_start..]) {
+      let i = chunk_start + i;
+
+      if chunk_start < i {
+        f.write_str(&s[chunk_start..i])?;
+      }
+
+      f.write_str(replacement(bytes[i]))?;
+
+      chunk_start = i + 1;
+    }
+
+    if chunk_start < s.len() {
+      f.write_str(&s[chunk_start..])?;
+    }
+
+    Ok(())
+  }
+}
+
+const WORD: usize = size_of::<usize>();
+
+/// `0x01` in every byte of a word.
+const LO: usize = usize::MAX / 0xFF;
+
+/// `0x80` in every byte of a word.
+const HI: usize = LO * 0x80;
+
+/// Find the first character in `haystack` which must be escaped.
+///
+/// Bytes are checked a word at a time, using bitwise operations which detect
+/// if any byte in the word is equal to a needle, so long runs of bytes which
+/// don't need to be escaped are skipped quickly.
+#[inline]
+fn find(haystack: &[u8]) -> Option<usize> {
+  /// High bit of a byte in the result is set if the same byte of `word` is
+  /// equal to `needle`, ignoring bytes above the first match.
+  fn matches(word: usize, needle: u8) -> usize {
+    let word = word ^ (LO * usize::from(needle));
+    word.wrapping_sub(LO) & !word & HI
+  }
+
+  // special bytes are often close together, so check the first word's worth
+  // of bytes individually
+  let head = haystack.len().min(WORD);
+
+  if let Some(i) = haystack[..head]
+    .iter()
+    .position(|&byte| ESCAPE[usize::from(byte)])
+  {
+    return Some(i);
+  }
+
+  let mut chunks = haystack[head..].chunks_exact(WORD);
+
+  for (i, chunk) in chunks.by_ref().enumerate() {
+    // little-endian, so that less significant bytes come first in memory
+    let word = usize::from_le_bytes(chunk.try_into().unwrap());
+
+    let mask = matches(word, b'"')
+      | matches(word, b'&')
+      | matches(word, b'\'')
+      | matches(word, b'<')
+      | matches(word, b'>');
+
+    if mask != 0 {
+      // the lowest set bit is exact, since false positives only occur in
+      // bytes more significant than a match
+      return Some(head + i * WORD + mask.trailing_zeros() as usize / 8);
+    }
+  }
+
+  let offset = haystack.len() - chunks.remainder().len();
+
+  chunks
+    .remainder()
+    .iter()
+    .position(|&byte| ESCAPE[usize::from(byte)])
+    .map(|i| offset + i)
+}
+
+/// Bytes which must be escaped.
+static ESCAPE: [bool; 256] = {
+  let mut table = [false; 256];
+  table[b'"' as usize] = true;
+  table[b'&' as usize] = true;
+  table[b'\'' as usize] = true;
+  table[b'<' as usize] = true;
+  table[b'>' as usize] = true;
+  table
+};
+
+#[inline]
+fn replacement(byte: u8) -> &'static str {
+  match byte {
+    b'"' => "&quot;",
+    b'&' => "&amp;",
+    b'\'' => "&apos;",
+    b'<' => "&lt;",
+    b'>' => "&gt;",
+    _ => unreachable!(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, alloc::string::String};
+
+  #[track_caller]
+  fn case(value: &str, expected: &str) {
+    let mut output = String::new();
+    Html.escape(&mut output, value).unwrap();
+    assert_eq!(output, expected);
+  }
+
+  #[test]
+  fn escape() {
+    case("", "");
+    case("hello", "hello");
+    case("\n", "\n");
+    case("\"", "&quot;");
+    case("&", "&amp;");
+    case("'", "&apos;");
+    case("<", "&lt;");
+    case(">", "&gt;");
+    case("foo&bar&baz", "foo&amp;bar&amp;baz");
+  }
+
+  #[test]
+  fn long_runs() {
+    case(
+      "a bunch of text <b>bold</b> & more\ntext",
+      "a bunch of text &lt;b&gt;bold&lt;/b&gt; &amp; more\ntext",
+    );
+  }
+
+  #[test]
+  fn find_special() {
+    #[track_caller]
+    fn case(haystack: &str, expected: Option<usize>) {
+      assert_eq!(find(haystack.as_bytes()), expected);
+    }
+
+    case("", None);
+    case("a", None);
+    case("\n", None);
+
+    for special in ["\"", "&", "'", "<", ">"] {
+      for len in 0..40 {
+        let mut haystack = "a".repeat(len);
+        haystack.push_str(special);
+        haystack.push_str("b&\nc");
+        case(&haystack, Some(len));
+      }
+    }
+
+    for len in 0..40 {
+      case(&"a".repeat(len), None);
+      case(&"é".repeat(len), None);
+      case(&"\n".repeat(len), None);
+    }
+
+    case("aaaaaaaaaaaaaa\u{80}\u{ff}", None);
+  }
+
+  #[test]
+  fn format() {
+    let mut output = String::new();
+    Html.format(&mut output, &format_args!("<{}>", 1)).unwrap();
+    assert_eq!(output, "&lt;1&gt;");
+  }
+}
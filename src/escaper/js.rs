@@ -0,0 +1,54 @@
+use super::*;
+
+/// Escapes values for inclusion in quoted JavaScript strings, using unicode
+/// escapes.
+pub struct Js;
+
+impl Escaper for Js {
+  fn escape(&self, f: &mut dyn Write, s: &str) -> fmt::Result {
+    let mut start = 0;
+
+    for (i, c) in s.char_indices() {
+      if !matches!(
+        c,
+        '\0'
+          ..='\x1f'
+            | '"'
+            | '$'
+            | '&'
+            | '\''
+            | '<'
+            | '>'
+            | '\\'
+            | '`'
+            | '\u{7f}'
+            | '\u{2028}'
+            | '\u{2029}'
+      ) {
+        continue;
+      }
+
+      f.write_str(&s[start..i])?;
+      write!(f, "\\u{:04x}", u32::from(c))?;
+      start = i + c.len_utf8();
+    }
+
+    f.write_str(&s[start..])
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, alloc::string::String};
+
+  #[test]
+  fn escape() {
+    let mut output = String::new();
+    Js.escape(&mut output, "a b\"c'd\\e`f${g}</script>\n\u{2028}é")
+      .unwrap();
+    assert_eq!(
+      output,
+      "a b\\u0022c\\u0027d\\u005ce\\u0060f\\u0024{g}\\u003c/script\\u003e\\u000a\\u2028é",
+    );
+  }
+}
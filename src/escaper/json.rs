@@ -0,0 +1,55 @@
+use super::*;
+
+/// Escapes values for inclusion in JSON strings.
+///
+/// `<`, `>`, and `&` are also escaped, so that the output may be embedded in
+/// HTML, as are U+2028 and U+2029, which are not valid in JavaScript strings
+/// in older engines.
+pub struct Json;
+
+impl Escaper for Json {
+  fn escape(&self, f: &mut dyn Write, s: &str) -> fmt::Result {
+    let mut start = 0;
+
+    for (i, c) in s.char_indices() {
+      let replacement = match c {
+        '"' => "\\\"",
+        '\\' => "\\\\",
+        '\n' => "\\n",
+        '\r' => "\\r",
+        '\t' => "\\t",
+        '\0'..='\x1f' | '&' | '<' | '>' | '\u{2028}' | '\u{2029}' => "",
+        _ => continue,
+      };
+
+      f.write_str(&s[start..i])?;
+
+      if replacement.is_empty() {
+        write!(f, "\\u{:04x}", u32::from(c))?;
+      } else {
+        f.write_str(replacement)?;
+      }
+
+      start = i + c.len_utf8();
+    }
+
+    f.write_str(&s[start..])
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, alloc::string::String};
+
+  #[test]
+  fn escape() {
+    let mut output = String::new();
+    Json
+      .escape(&mut output, "a \"b\" \\c\n\r\t\x01</script>&é\u{2028}")
+      .unwrap();
+    assert_eq!(
+      output,
+      "a \\\"b\\\" \\\\c\\n\\r\\t\\u0001\\u003c/script\\u003e\\u0026é\\u2028",
+    );
+  }
+}
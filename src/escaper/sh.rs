@@ -0,0 +1,68 @@
+use super::*;
+
+/// Quotes values as single shell words, by wrapping them in single quotes.
+///
+/// Single quotes in the value are written as `'\''`, which closes the quoted
+/// string, writes an escaped single quote, and opens a new quoted string.
+pub struct Sh;
+
+impl Escaper for Sh {
+  fn escape(&self, f: &mut dyn Write, s: &str) -> fmt::Result {
+    f.write_char('\'')?;
+    Quoted(f).write_str(s)?;
+    f.write_char('\'')
+  }
+
+  /// The value is quoted as a whole, rather than each piece, so empty values
+  /// are written as `''`.
+  fn format(&self, f: &mut dyn Write, value: &dyn Display) -> fmt::Result {
+    f.write_char('\'')?;
+    write!(Quoted(f), "{value}")?;
+    f.write_char('\'')
+  }
+}
+
+/// Escapes single quotes inside of a single-quoted string.
+struct Quoted<'a>(&'a mut dyn Write);
+
+impl Write for Quoted<'_> {
+  fn write_str(&mut self, s: &str) -> fmt::Result {
+    for (i, part) in s.split('\'').enumerate() {
+      if i > 0 {
+        self.0.write_str("'\\''")?;
+      }
+      self.0.write_str(part)?;
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, alloc::string::String};
+
+  #[track_caller]
+  fn case(value: &dyn Display, expected: &str) {
+    let mut output = String::new();
+    Sh.format(&mut output, value).unwrap();
+    assert_eq!(output, expected);
+  }
+
+  #[test]
+  fn format() {
+    case(&"", "''");
+    case(&"foo", "'foo'");
+    case(&"foo bar", "'foo bar'");
+    case(&"$HOME `ls` \"\\", "'$HOME `ls` \"\\'");
+    case(&"it's", "'it'\\''s'");
+    case(&"''", "''\\'''\\'''");
+    case(&format_args!("{}'{}", "a", "b"), "'a'\\''b'");
+  }
+
+  #[test]
+  fn escape() {
+    let mut output = String::new();
+    Sh.escape(&mut output, "it's").unwrap();
+    assert_eq!(output, "'it'\\''s'");
+  }
+}
@@ -0,0 +1,52 @@
+use super::*;
+
+/// Escapes the characters which are special in LaTeX text.
+pub struct Tex;
+
+impl Escaper for Tex {
+  fn escape(&self, f: &mut dyn Write, s: &str) -> fmt::Result {
+    let mut start = 0;
+
+    for (i, c) in s.char_indices() {
+      let replacement = match c {
+        '#' => "\\#",
+        '$' => "\\$",
+        '%' => "\\%",
+        '&' => "\\&",
+        '\\' => "\\textbackslash{}",
+        '^' => "\\textasciicircum{}",
+        '_' => "\\_",
+        '{' => "\\{",
+        '}' => "\\}",
+        '~' => "\\textasciitilde{}",
+        _ => continue,
+      };
+
+      f.write_str(&s[start..i])?;
+      f.write_str(replacement)?;
+      start = i + c.len_utf8();
+    }
+
+    f.write_str(&s[start..])
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, alloc::string::String};
+
+  #[test]
+  fn escape() {
+    let mut output = String::new();
+    Tex
+      .escape(&mut output, "50% of $x_1 & {y} #2 ~ ^ \\ é")
+      .unwrap();
+    assert_eq!(
+      output,
+      concat!(
+        "50\\% of \\$x\\_1 \\& \\{y\\} \\#2 ",
+        "\\textasciitilde{} \\textasciicircum{} \\textbackslash{} é",
+      ),
+    );
+  }
+}
@@ -0,0 +1,90 @@
+use super::*;
+
+/// Escapes URLs for inclusion in quoted HTML attribute values.
+///
+/// URLs with schemes other than `http`, `https`, and `mailto`, like
+/// `javascript`, may execute code when followed, and are replaced with
+/// `about:invalid#boilerplate`.
+pub struct Url;
+
+impl Url {
+  /// Replacement for URLs with unsafe schemes.
+  const UNSAFE: &str = "about:invalid#boilerplate";
+
+  /// Whether `url` is relative, or has a safe scheme.
+  fn is_safe(url: &str) -> bool {
+    let Some(colon) = url.find(':') else {
+      return true;
+    };
+
+    let scheme = &url[..colon];
+
+    if scheme.contains(['/', '?', '#']) {
+      return true;
+    }
+
+    ["http", "https", "mailto"]
+      .iter()
+      .any(|safe| scheme.eq_ignore_ascii_case(safe))
+  }
+}
+
+impl Escaper for Url {
+  fn escape(&self, f: &mut dyn Write, s: &str) -> fmt::Result {
+    Html.escape(f, if Self::is_safe(s) { s } else { Self::UNSAFE })
+  }
+
+  /// The scheme of the URL may be split across pieces of the formatted value,
+  /// so the value is formatted into a string before being checked.
+  fn format(&self, f: &mut dyn Write, value: &dyn Display) -> fmt::Result {
+    self.escape(f, &value.to_string())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, alloc::string::String};
+
+  #[test]
+  fn safe() {
+    #[track_caller]
+    fn case(url: &str, expected: bool) {
+      assert_eq!(Url::is_safe(url), expected, "{url}");
+    }
+
+    case("", true);
+    case("/foo", true);
+    case("foo/bar:baz", true);
+    case("?foo:bar", true);
+    case("#foo:bar", true);
+    case("http://example.com", true);
+    case("HTTPS://example.com", true);
+    case("mailto:bob@example.com", true);
+    case("javascript:alert(1)", false);
+    case("JavaScript:alert(1)", false);
+    case(" javascript:alert(1)", false);
+    case("java\tscript:alert(1)", false);
+    case("data:text/html,foo", false);
+    case("vbscript:foo", false);
+  }
+
+  #[test]
+  fn format() {
+    #[track_caller]
+    fn case(value: &dyn Display, expected: &str) {
+      let mut output = String::new();
+      Url.format(&mut output, value).unwrap();
+      assert_eq!(output, expected);
+    }
+
+    case(
+      &"https://example.com/?a=b&c=d",
+      "https://example.com/?a=b&amp;c=d",
+    );
+    case(&"javascript:alert(1)", "about:invalid#boilerplate");
+    case(
+      &format_args!("java{}", "script:alert(1)"),
+      "about:invalid#boilerplate",
+    );
+  }
+}
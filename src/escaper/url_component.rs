@@ -0,0 +1,44 @@
+use super::*;
+
+/// Percent-encodes values for inclusion in URL components, like query
+/// parameters and path segments.
+///
+/// All bytes except ASCII alphanumerics and `-`, `.`, `_`, and `~` are
+/// encoded.
+pub struct UrlComponent;
+
+impl Escaper for UrlComponent {
+  fn escape(&self, f: &mut dyn Write, s: &str) -> fmt::Result {
+    let mut start = 0;
+
+    for (i, c) in s.char_indices() {
+      if c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_' | '~') {
+        continue;
+      }
+
+      f.write_str(&s[start..i])?;
+
+      for byte in c.encode_utf8(&mut [0; 4]).bytes() {
+        write!(f, "%{byte:02X}")?;
+      }
+
+      start = i + c.len_utf8();
+    }
+
+    f.write_str(&s[start..])
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, alloc::string::String};
+
+  #[test]
+  fn escape() {
+    let mut output = String::new();
+    UrlComponent
+      .escape(&mut output, "a-b_c.d~e f&g=h/i?é")
+      .unwrap();
+    assert_eq!(output, "a-b_c.d~e%20f%26g%3Dh%2Fi%3F%C3%A9");
+  }
+}
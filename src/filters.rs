@@ -0,0 +1,183 @@
+//! Built-in filters, applied to interpolations with `{{ value |> filter }}`.
+//!
+//! Filters are functions which take a value implementing `Display`, and any
+//! arguments given with `{{ value |> filter(args...) }}`, and return a new
+//! value implementing `Display`. Filters other than the built-in filters are
+//! called from a `filters` module in scope where the template is used.
+//!
+//! Escaping filters, like [`json`], escape the value with the corresponding
+//! escaper from `boilerplate::escaper`. The output of filters is escaped by
+//! the template's escaper like any other value, unless the last filter is
+//! `trusted`, which disables escaping.
+
+use {
+  super::*,
+  escaper::{Css, Html, Js, Json, Sh, Tex, Url, UrlComponent},
+};
+
+/// A value escaped by an escaper.
+struct Escape<E, T>(E, T);
+
+impl<E: Escaper, T: Display> Display for Escape<E, T> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    self.0.format(f, &self.1)
+  }
+}
+
+/// A value with the case of each character converted.
+struct Case<T> {
+  upper: bool,
+  value: T,
+}
+
+impl<T: Display> Display for Case<T> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    struct Writer<'a, 'b> {
+      inner: &'a mut fmt::Formatter<'b>,
+      upper: bool,
+    }
+
+    impl Write for Writer<'_, '_> {
+      fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+          if self.upper {
+            for c in c.to_uppercase() {
+              self.inner.write_char(c)?;
+            }
+          } else {
+            for c in c.to_lowercase() {
+              self.inner.write_char(c)?;
+            }
+          }
+        }
+        Ok(())
+      }
+    }
+
+    write!(
+      Writer {
+        inner: f,
+        upper: self.upper,
+      },
+      "{}",
+      self.value,
+    )
+  }
+}
+
+/// Escape `value` with [`Css`].
+#[must_use]
+pub fn css(value: impl Display) -> impl Display {
+  Escape(Css, value)
+}
+
+/// Escape `value` with [`Html`].
+#[must_use]
+pub fn html(value: impl Display) -> impl Display {
+  Escape(Html, value)
+}
+
+/// Escape `value` with [`Js`].
+#[must_use]
+pub fn js(value: impl Display) -> impl Display {
+  Escape(Js, value)
+}
+
+/// Escape `value` with [`Json`].
+#[must_use]
+pub fn json(value: impl Display) -> impl Display {
+  Escape(Json, value)
+}
+
+/// Convert `value` to lowercase.
+#[must_use]
+pub fn lower(value: impl Display) -> impl Display {
+  Case {
+    upper: false,
+    value,
+  }
+}
+
+/// Escape `value` with [`Sh`].
+#[must_use]
+pub fn sh(value: impl Display) -> impl Display {
+  Escape(Sh, value)
+}
+
+/// Escape `value` with [`Tex`].
+#[must_use]
+pub fn tex(value: impl Display) -> impl Display {
+  Escape(Tex, value)
+}
+
+/// Remove leading and trailing whitespace from `value`.
+#[must_use]
+pub fn trim(value: impl Display) -> impl Display {
+  value.to_string().trim().to_string()
+}
+
+/// Truncate `value` to `length` characters, replacing the last character with
+/// `…` if it was truncated.
+#[must_use]
+pub fn truncate(value: impl Display, length: usize) -> impl Display {
+  let mut value = value.to_string();
+
+  if let Some((i, _)) = value.char_indices().nth(length) {
+    value.truncate(i);
+    if value.pop().is_some() {
+      value.push('…');
+    }
+  }
+
+  value
+}
+
+/// Convert `value` to uppercase.
+#[must_use]
+pub fn upper(value: impl Display) -> impl Display {
+  Case { upper: true, value }
+}
+
+/// Escape `value` with [`Url`].
+#[must_use]
+pub fn url(value: impl Display) -> impl Display {
+  Escape(Url, value)
+}
+
+/// Escape `value` with [`UrlComponent`].
+#[must_use]
+pub fn urlencode(value: impl Display) -> impl Display {
+  Escape(UrlComponent, value)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn case() {
+    assert_eq!(upper("Straße").to_string(), "STRASSE");
+    assert_eq!(lower("HeLLo").to_string(), "hello");
+  }
+
+  #[test]
+  fn escape() {
+    assert_eq!(json("\"").to_string(), "\\\"");
+    assert_eq!(urlencode("a b").to_string(), "a%20b");
+    assert_eq!(sh("").to_string(), "''");
+  }
+
+  #[test]
+  fn trim() {
+    assert_eq!(super::trim("  foo \n").to_string(), "foo");
+  }
+
+  #[test]
+  fn truncate() {
+    assert_eq!(super::truncate("hello", 10).to_string(), "hello");
+    assert_eq!(super::truncate("hello", 5).to_string(), "hello");
+    assert_eq!(super::truncate("hello", 4).to_string(), "hel…");
+    assert_eq!(super::truncate("héllo", 3).to_string(), "hé…");
+    assert_eq!(super::truncate("hello", 0).to_string(), "");
+  }
+}
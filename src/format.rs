@@ -1,8 +1,63 @@
 use super::*;
 
+/// Write an interpolated value.
+///
+/// Interpolations in templates with escaping are written with `Format`, which
+/// is implemented for all types which implement `Display`, as well as
+/// [`Trusted`] and [`Escaped`], which override the template's escaper.
+///
+/// Types which do not implement `Display` may implement `Format` directly,
+/// to control how they are escaped and indented:
+///
+/// ```
+/// use {
+///   boilerplate::{Escaper, Format, Formatter},
+///   core::fmt::{self, Write},
+/// };
+///
+/// /// A list, written one item per line, with each item escaped.
+/// struct List(Vec<&'static str>);
+///
+/// impl Format for List {
+///   fn format(
+///     &self,
+///     f: &mut dyn Write,
+///     escaper: &dyn Escaper,
+///     indent: &str,
+///     trim: bool,
+///   ) -> fmt::Result {
+///     let mut f = Formatter::new(f).indent(indent).trim(trim);
+///     for item in &self.0 {
+///       f.write_str("<li>")?;
+///       escaper.escape(&mut f, item)?;
+///       f.write_str("</li>\n")?;
+///     }
+///     Ok(())
+///   }
+/// }
+///
+/// #[derive(boilerplate::Boilerplate)]
+/// #[boilerplate(text = "<ul>\n  {{ self.0 }}\n</ul>\n")]
+/// struct ListHtml(List);
+///
+/// assert_eq!(
+///   ListHtml(List(vec!["a", "<b>"])).to_string(),
+///   "<ul>\n  <li>a</li>\n  <li>&lt;b&gt;</li>\n</ul>\n",
+/// );
+/// ```
 pub trait Format {
-  /// Write `self` to `f`, escaping if necessary, indenting continuation lines
-  /// by `indent`, and dropping a single trailing newline from the output if
-  /// `trim` is true.
-  fn format(&self, f: &mut fmt::Formatter, indent: &'static str, trim: bool) -> fmt::Result;
+  /// Write `self` to `f`, escaping with `escaper` if necessary, indenting
+  /// continuation lines by `indent`, and dropping a single trailing newline
+  /// from the output if `trim` is true.
+  ///
+  /// `indent` is the whitespace before the interpolation, if it is the first
+  /// thing on its line, and `trim` is true if the interpolation is followed by
+  /// a newline in the template.
+  fn format(
+    &self,
+    f: &mut dyn Write,
+    escaper: &dyn Escaper,
+    indent: &str,
+    trim: bool,
+  ) -> fmt::Result;
 }
@@ -1,30 +1,94 @@
 use super::*;
 
-/// A `core::fmt::Write` adapter that optionally HTML-escapes its input,
-/// optionally appends `indent` after every internal `\n`, and optionally
-/// buffers a trailing `\n` so that it can be silently dropped on `Drop`.
-#[allow(clippy::struct_excessive_bools)]
-pub struct Formatter<'a, W: ?Sized> {
-  escape: bool,
-  indent: &'static str,
-  inner: &'a mut W,
+/// A `core::fmt::Write` adapter which indents, trims, and escapes its input,
+/// used to write interpolated values.
+///
+/// - If an indent is set, it is written after each `\n`, so that values with
+///   multiple lines are indented to the same level as the first line. The
+///   indent is pending until more output is written, so it is never written at
+///   the end of the output, and lines do not end with trailing whitespace.
+///
+/// - If trimming is enabled, each `\n` is pending until more output is
+///   written, so a single trailing `\n` is never written. This is used to
+///   avoid writing a blank line when an interpolation which ends with a `\n`
+///   is followed by a `\n` in the template.
+///
+/// - If an escaper is set, output other than newlines and indentation is
+///   escaped with it.
+///
+/// With none of these options, input is written unchanged:
+///
+/// ```
+/// use {boilerplate::Formatter, core::fmt::Write};
+///
+/// let mut output = String::new();
+/// write!(Formatter::new(&mut output), "foo\nbar\n").unwrap();
+/// assert_eq!(output, "foo\nbar\n");
+/// ```
+///
+/// Options are set with builder methods:
+///
+/// ```
+/// use {
+///   boilerplate::{Formatter, escaper::Html},
+///   core::fmt::Write,
+/// };
+///
+/// let mut output = String::new();
+/// write!(
+///   Formatter::new(&mut output).indent("  ").trim(true).escaper(&Html),
+///   "<foo>\n<bar>\n",
+/// )
+/// .unwrap();
+/// assert_eq!(output, "&lt;foo&gt;\n  &lt;bar&gt;");
+/// ```
+///
+/// Output is written as soon as possible, so a `Formatter` can be dropped at
+/// any time, after which pending newlines and indents are discarded.
+pub struct Formatter<'a> {
+  escaper: Option<&'a dyn Escaper>,
+  indent: &'a str,
+  inner: &'a mut dyn Write,
   pending_indent: bool,
   pending_newline: bool,
   trim: bool,
 }
 
-impl<'a, W: Write + ?Sized> Formatter<'a, W> {
-  pub fn new(inner: &'a mut W, escape: bool, indent: &'static str, trim: bool) -> Self {
+impl<'a> Formatter<'a> {
+  /// Create a new `Formatter` which writes to `inner`, with no indent, no
+  /// trimming, and no escaping.
+  pub fn new(inner: &'a mut dyn Write) -> Self {
     Self {
-      escape,
-      indent,
+      escaper: None,
+      indent: "",
       inner,
       pending_indent: false,
       pending_newline: false,
-      trim,
+      trim: false,
+    }
+  }
+
+  /// Escape output with `escaper`.
+  #[must_use]
+  pub fn escaper(self, escaper: &'a dyn Escaper) -> Self {
+    Self {
+      escaper: Some(escaper),
+      ..self
     }
   }
 
+  /// Write `indent` after each `\n`, before any following output.
+  #[must_use]
+  pub fn indent(self, indent: &'a str) -> Self {
+    Self { indent, ..self }
+  }
+
+  /// If `trim` is true, don't write a single trailing `\n`.
+  #[must_use]
+  pub fn trim(self, trim: bool) -> Self {
+    Self { trim, ..self }
+  }
+
   fn flush_newline(&mut self) -> fmt::Result {
     if self.pending_newline {
       self.inner.write_str("\n")?;
@@ -47,39 +111,32 @@ impl<'a, W: Write + ?Sized> Formatter<'a, W> {
     self.flush_indent()?;
     Ok(())
   }
+
+  fn write_chunk(&mut self, s: &str) -> fmt::Result {
+    match self.escaper {
+      Some(escaper) => escaper.escape(self.inner, s),
+      None => self.inner.write_str(s),
+    }
+  }
 }
 
-impl<W: Write + ?Sized> Write for Formatter<'_, W> {
+impl Write for Formatter<'_> {
   fn write_str(&mut self, s: &str) -> fmt::Result {
-    let mut chunk_start = 0;
+    if !self.trim && self.indent.is_empty() {
+      return self.write_chunk(s);
+    }
 
-    for (i, &byte) in s.as_bytes().iter().enumerate() {
-      let replacement = if self.escape {
-        match byte {
-          b'"' => Some("&quot;"),
-          b'&' => Some("&amp;"),
-          b'<' => Some("&lt;"),
-          b'>' => Some("&gt;"),
-          b'\'' => Some("&apos;"),
-          _ => None,
-        }
-      } else {
-        None
-      };
+    let mut chunk_start = 0;
 
-      if byte != b'\n' && replacement.is_none() {
-        continue;
-      }
+    while let Some(i) = s[chunk_start..].find('\n') {
+      let i = chunk_start + i;
 
       if chunk_start < i {
         self.flush()?;
-        self.inner.write_str(&s[chunk_start..i])?;
+        self.write_chunk(&s[chunk_start..i])?;
       }
 
-      if let Some(replacement) = replacement {
-        self.flush()?;
-        self.inner.write_str(replacement)?;
-      } else if self.trim {
+      if self.trim {
         self.flush_newline()?;
         self.pending_newline = true;
       } else {
@@ -92,7 +149,7 @@ impl<W: Write + ?Sized> Write for Formatter<'_, W> {
 
     if chunk_start < s.len() {
       self.flush()?;
-      self.inner.write_str(&s[chunk_start..])?;
+      self.write_chunk(&s[chunk_start..])?;
     }
 
     Ok(())
@@ -103,12 +160,11 @@ impl<W: Write + ?Sized> Write for Formatter<'_, W> {
 mod tests {
   use {
     super::*,
+    alloc::string::ToString,
     core::fmt::{self, Display},
+    escaper::Html,
   };
 
-  #[cfg(not(feature = "reload"))]
-  use alloc::string::ToString;
-
   struct Wrapper {
     escape: bool,
     indent: &'static str,
@@ -118,7 +174,10 @@ mod tests {
 
   impl Display for Wrapper {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-      let mut f = Formatter::new(f, self.escape, self.indent, self.trim);
+      let mut f = Formatter::new(f).indent(self.indent).trim(self.trim);
+      if self.escape {
+        f = f.escaper(&Html);
+      }
       f.write_str(self.value)
     }
   }
@@ -216,7 +275,7 @@ mod tests {
 
     impl Display for Sink {
       fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut f = Formatter::new(f, false, "  ", false);
+        let mut f = Formatter::new(f).indent("  ");
         f.write_str("a\n")?;
         f.write_str("b")?;
         f.write_str("\nc\n")?;
@@ -233,7 +292,7 @@ mod tests {
 
     impl Display for Sink {
       fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut f = Formatter::new(f, false, "  ", false);
+        let mut f = Formatter::new(f).indent("  ");
         f.write_str("a\n")?;
         f.write_str("b")?;
         Ok(())
@@ -243,6 +302,22 @@ mod tests {
     assert_eq!(Sink.to_string(), "a\n  b");
   }
 
+  #[test]
+  fn long_runs() {
+    case(
+      true,
+      "  ",
+      "a bunch of text <b>bold</b> & more\ntext",
+      "a bunch of text &lt;b&gt;bold&lt;/b&gt; &amp; more\n  text",
+    );
+    case(
+      false,
+      "",
+      "a bunch of text <b>bold</b> & more\ntext",
+      "a bunch of text <b>bold</b> & more\ntext",
+    );
+  }
+
   #[test]
   fn trim_drops_trailing_newline() {
     trim_case(false, "", "a\n", "a");
@@ -277,7 +352,7 @@ mod tests {
 
     impl Display for Sink {
       fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut f = Formatter::new(f, false, "  ", true);
+        let mut f = Formatter::new(f).indent("  ").trim(true);
         f.write_str("a\n")?;
         f.write_str("b\n")?;
         Ok(())
@@ -0,0 +1,83 @@
+use super::*;
+
+/// Marker for values which are safe to include in HTML without escaping.
+///
+/// `#[derive(Boilerplate)]` implements `HtmlSafe` for templates with an
+/// `html` or `htm` extension. Values which implement `HtmlSafe` are not
+/// escaped when interpolated into the content of an element in another HTML
+/// template, so that templates can be nested without escaping them twice:
+///
+/// ```
+/// #[derive(boilerplate::Boilerplate)]
+/// #[boilerplate(text = "<li>{{ self.0 }}</li>")]
+/// struct ItemHtml(&'static str);
+///
+/// #[derive(boilerplate::Boilerplate)]
+/// #[boilerplate(text = "<ul>{{ self.0 }}{{ self.1 }}</ul>")]
+/// struct ListHtml(ItemHtml, &'static str);
+///
+/// assert_eq!(
+///   ListHtml(ItemHtml("<a>"), "<b>").to_string(),
+///   "<ul><li>&lt;a&gt;</li>&lt;b&gt;</ul>",
+/// );
+/// ```
+///
+/// `HtmlSafe` values are escaped as usual in attribute values, `<script>` and
+/// `<style>` elements, and other contexts.
+pub trait HtmlSafe: Display {}
+
+impl<T: HtmlSafe + ?Sized> HtmlSafe for &T {}
+
+/// An interpolated value in HTML element content. Generated code calls
+/// `interpolate` on `&Interpolation`, which resolves to
+/// `InterpolateHtmlSafe::interpolate` if the value implements `HtmlSafe`, and
+/// otherwise to `Interpolate::interpolate`, which requires an additional
+/// autoref.
+#[doc(hidden)]
+pub struct Interpolation<'a, T: ?Sized>(pub &'a T);
+
+#[doc(hidden)]
+pub trait InterpolateHtmlSafe {
+  fn interpolate(
+    &self,
+    f: &mut dyn Write,
+    escaper: &dyn Escaper,
+    indent: &str,
+    trim: bool,
+  ) -> fmt::Result;
+}
+
+impl<T: HtmlSafe + ?Sized> InterpolateHtmlSafe for Interpolation<'_, T> {
+  fn interpolate(
+    &self,
+    f: &mut dyn Write,
+    _escaper: &dyn Escaper,
+    indent: &str,
+    trim: bool,
+  ) -> fmt::Result {
+    write!(Formatter::new(f).indent(indent).trim(trim), "{}", self.0)
+  }
+}
+
+#[doc(hidden)]
+pub trait Interpolate {
+  fn interpolate(
+    &self,
+    f: &mut dyn Write,
+    escaper: &dyn Escaper,
+    indent: &str,
+    trim: bool,
+  ) -> fmt::Result;
+}
+
+impl<T: Format + ?Sized> Interpolate for &Interpolation<'_, T> {
+  fn interpolate(
+    &self,
+    f: &mut dyn Write,
+    escaper: &dyn Escaper,
+    indent: &str,
+    trim: bool,
+  ) -> fmt::Result {
+    self.0.format(f, escaper, indent, trim)
+  }
+}
@@ -0,0 +1,43 @@
+use super::*;
+
+/// A `core::fmt::Write` adapter that writes to an `std::io::Write`, saving the
+/// first I/O error, which would otherwise be lost when it is converted to a
+/// `fmt::Error`.
+pub(crate) struct IoWriter<W> {
+  pub(crate) error: Option<io::Error>,
+  pub(crate) inner: W,
+}
+
+impl<W: io::Write> Write for IoWriter<W> {
+  fn write_str(&mut self, s: &str) -> fmt::Result {
+    self.inner.write_all(s.as_bytes()).map_err(|err| {
+      self.error.get_or_insert(err);
+      fmt::Error
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn write() {
+    let mut writer = IoWriter {
+      error: None,
+      inner: Vec::new(),
+    };
+    write!(writer, "foo {}", 1).unwrap();
+    assert_eq!(writer.inner, b"foo 1");
+  }
+
+  #[test]
+  fn error() {
+    let mut writer = IoWriter {
+      error: None,
+      inner: &mut [0u8; 2][..],
+    };
+    assert_eq!(write!(writer, "foo"), Err(fmt::Error));
+    assert_eq!(writer.error.unwrap().kind(), io::ErrorKind::WriteZero);
+  }
+}
@@ -21,8 +21,8 @@
 //! the local scope when rendered.
 //!
 //! Use `boilerplate::Boilerplate` if you want to put your template text in a
-//! separate file, or if you need HTML escaping, and `boilerplate::boilerplate`
-//! if you want to put your template in a string literal.
+//! separate file, and `boilerplate::boilerplate` if you want to put your
+//! template in a string literal.
 //!
 //! `boilerplate` is very simple, requires no runtime dependencies, and is
 //! usable in a `no_std` environment.
@@ -80,6 +80,39 @@
 //! assert_eq!(Context { n: 10 }.to_string(), "Foo is 10!\n");
 //! ```
 //!
+//! Template Directory
+//! ------------------
+//!
+//! The `templates` directory, which template filenames and includes are
+//! relative to, can be changed for all templates in a crate with `templates`
+//! in the `[package.metadata.boilerplate]` table of `Cargo.toml`, for example,
+//! to use templates in an `assets` directory shared by crates in a workspace:
+//!
+//! ```toml
+//! [package.metadata.boilerplate]
+//! templates = "../assets"
+//! ```
+//!
+//! Unknown keys in `[package.metadata.boilerplate]` are an error.
+//!
+//! The `BOILERPLATE_TEMPLATES` environment variable, if set, takes precedence
+//! over `Cargo.toml`. Relative paths are relative to the crate root.
+//!
+//! Custom Path
+//! -----------
+//!
+//! Template files outside of the templates directory can be used with `path`,
+//! which is relative to the crate root, or absolute:
+//!
+//! ```
+//! #[derive(boilerplate::Boilerplate)]
+//! #[boilerplate(path = "templates/quick-start.txt")]
+//! struct Context {
+//!   n: u32,
+//! }
+//! assert_eq!(Context { n: 10 }.to_string(), "Foo is 10!\n");
+//! ```
+//!
 //! Inline Templates
 //! ----------------
 //!
@@ -92,6 +125,64 @@
 //! assert_eq!(Context {}.to_string(), "Hello, world!");
 //! ```
 //!
+//! Rendering
+//! ---------
+//!
+//! Templates can be rendered to a new `String` with `.to_string()`. To avoid
+//! allocating a new `String`, `Boilerplate::render_into` appends a template to
+//! an existing `String`:
+//!
+//! ```
+//! use boilerplate::Boilerplate;
+//!
+//! #[derive(Boilerplate)]
+//! #[boilerplate(text = "Hello, {{ self.0 }}!\n")]
+//! struct Context(&'static str);
+//!
+//! let mut output = String::new();
+//! Context("Bob").render_into(&mut output).unwrap();
+//! Context("Alice").render_into(&mut output).unwrap();
+//! assert_eq!(output, "Hello, Bob!\nHello, Alice!\n");
+//! ```
+//!
+//! `Boilerplate::render` and `Boilerplate::render_into` preallocate
+//! `Boilerplate::SIZE_HINT` bytes, the total length of the template's text plus
+//! an estimate of 16 bytes for each interpolation. The estimate can be changed
+//! with the `interpolation_size_hint` attribute:
+//!
+//! ```
+//! use boilerplate::Boilerplate;
+//!
+//! #[derive(Boilerplate)]
+//! #[boilerplate(interpolation_size_hint = 100, text = "Hello, {{ self.0 }}!\n")]
+//! struct Context(&'static str);
+//!
+//! assert_eq!(Context::SIZE_HINT, 109);
+//!
+//! let output = Context("Bob").render().unwrap();
+//! assert_eq!(output, "Hello, Bob!\n");
+//! assert!(output.capacity() >= 109);
+//! ```
+//!
+//! When the `std` feature is enabled, `Boilerplate::render_to_io` renders a
+//! template directly to a `std::io::Write`, such as a file or socket, without
+//! rendering it to a `String` first:
+//!
+//! ```
+//! #[cfg(feature = "std")]
+//! {
+//!   use boilerplate::Boilerplate;
+//!
+//!   #[derive(Boilerplate)]
+//!   #[boilerplate(text = "Hello, {{ self.0 }}!\n")]
+//!   struct Context(&'static str);
+//!
+//!   let mut output = Vec::new();
+//!   Context("Bob").render_to_io(&mut output).unwrap();
+//!   assert_eq!(output, b"Hello, Bob!\n");
+//! }
+//! ```
+//!
 //! Guide
 //! -----
 //!
@@ -127,6 +218,17 @@
 //! assert_eq!(Context { name: "Bob" }.to_string(), "Hello, Bob!");
 //! ```
 //!
+//! Closing delimiters inside of string literals, character literals, and
+//! comments do not end a block, nor do `}}` which close braces opened inside
+//! of an interpolation:
+//!
+//! ```
+//! #[derive(boilerplate::Boilerplate)]
+//! #[boilerplate(text = r#"{{ format!("{{{}}}", 1) }} {{ match 1 { x => { x + 1 }} }}"#)]
+//! struct Context {}
+//! assert_eq!(Context {}.to_string(), "{1} 2");
+//! ```
+//!
 //! ### Interpolation Lines
 //!
 //! Expressions between `$$` and the next newline are interpolated into the
@@ -215,6 +317,151 @@
 //! assert_eq!(Context { }.to_string(), "");
 //! ```
 //!
+//! ### Comments
+//!
+//! Text inside of `{{#…#}}` is ignored:
+//!
+//! ```
+//! #[derive(boilerplate::Boilerplate)]
+//! #[boilerplate(text = "Hello, {{# greeting #}}world!")]
+//! struct Context {}
+//! assert_eq!(Context {}.to_string(), "Hello, world!");
+//! ```
+//!
+//! As is text between `$$#` and the next newline, including the newline:
+//!
+//! ```
+//! #[derive(boilerplate::Boilerplate)]
+//! #[boilerplate(text = "$$# greeting
+//! Hello, world!
+//! ")]
+//! struct Context {}
+//! assert_eq!(Context {}.to_string(), "Hello, world!\n");
+//! ```
+//!
+//! Since `#` cannot start a Rust expression, text which begins with `{{#` or
+//! `$$#` could not previously be part of a valid template, so comments never
+//! change the output of existing templates.
+//!
+//! ### Whitespace Control
+//!
+//! A `-` after the opening delimiter of a code block, interpolation block, or
+//! comment removes whitespace, including newlines, from the end of the
+//! preceding text, and a `-` before the closing delimiter removes whitespace
+//! from the start of the following text:
+//!
+//! ```
+//! #[derive(boilerplate::Boilerplate)]
+//! #[boilerplate(text = "<ul>
+//!   {%- for item in self.0 { %}
+//!   <li>{{ item }}</li>
+//!   {%- } %}
+//! </ul>
+//! ")]
+//! struct Context(&'static [&'static str]);
+//! assert_eq!(
+//!   Context(&["foo", "bar"]).to_string(),
+//!   "<ul>\n  <li>foo</li>\n  <li>bar</li>\n</ul>\n",
+//! );
+//! ```
+//!
+//! The `-` must be separated from the contents of the block by whitespace, so
+//! `{{-x}}` interpolates `-x`. Changing trim markers does not affect whether a
+//! reloaded template is compatible with the original.
+//!
+//! ### Literal Delimiters
+//!
+//! A doubled opening delimiter is included in the output once, verbatim:
+//!
+//! ```
+//! #[derive(boilerplate::Boilerplate)]
+//! #[boilerplate(text = "{{{{ name }} costs $$$$5")]
+//! struct Context {}
+//! assert_eq!(Context {}.to_string(), "{{ name }} costs $$5");
+//! ```
+//!
+//! Other text, including backslashes, is output verbatim, so LaTeX line breaks
+//! and Windows paths can precede blocks:
+//!
+//! ```
+//! #[derive(boilerplate::Boilerplate)]
+//! #[boilerplate(text = r"a \\{{ self.0 }} C:\{{ self.0 }}")]
+//! struct Context(&'static str);
+//! assert_eq!(Context("b").to_string(), r"a \\b C:\b");
+//! ```
+//!
+//! ### Custom Delimiters
+//!
+//! The `delimiters` attribute changes the delimiters of a template, which is
+//! useful for templates which generate text containing the default
+//! delimiters. Blocks take an open and close delimiter separated by
+//! whitespace, and line blocks take an open delimiter:
+//!
+//! ```
+//! #[derive(boilerplate::Boilerplate)]
+//! #[boilerplate(
+//!   delimiters(code = "<% %>", code_line = "%", interpolation = "<%= %>"),
+//!   text = "% for name in self.0 {\n<%= name %>: {{ <%= name %> }}\n% }\n",
+//! )]
+//! struct Context(&'static [&'static str]);
+//! assert_eq!(Context(&["foo", "bar"]).to_string(), "foo: {{ foo }}\nbar: {{ bar }}\n");
+//! ```
+//!
+//! Delimiters which are not given keep their defaults, so `{%`, `{{#`, `$$#` and
+//! `$$` are still recognized above. Blocks can be disabled with an empty
+//! string:
+//!
+//! ```
+//! #[derive(boilerplate::Boilerplate)]
+//! #[boilerplate(delimiters(interpolation_line = ""), text = "echo $$\n")]
+//! struct Context {}
+//! assert_eq!(Context {}.to_string(), "echo $$\n");
+//! ```
+//!
+//! The available blocks are `code`, `code_line`, `comment`, `comment_line`,
+//! `interpolation`, and `interpolation_line`.
+//!
+//! ### Includes
+//!
+//! Code blocks and code lines containing `include` followed by a path in
+//! double quotes are replaced with the contents of another template. Included
+//! template paths are relative to the `templates` directory, and included
+//! templates are parsed and escaped like the rest of the including template:
+//!
+//! ```
+//! #[derive(boilerplate::Boilerplate)]
+//! #[boilerplate(text = "<p>\n  {% include \"partials/greeting.txt\" %}\n</p>\n")]
+//! struct Context {
+//!   name: &'static str,
+//! }
+//! assert_eq!(Context { name: "Bob" }.to_string(), "<p>\n  Hello, Bob!\n</p>\n");
+//! ```
+//!
+//! If an include is preceded only by whitespace on its line, each line of the
+//! included template is indented with that whitespace. The final newline of a
+//! template included by a code block is removed, since the code block is
+//! usually followed by its own newline. Templates included by code lines keep
+//! their final newline:
+//!
+//! ```
+//! #[derive(boilerplate::Boilerplate)]
+//! #[boilerplate(text = "%% include \"partials/greeting.txt\"\nBye!\n")]
+//! struct Context {
+//!   name: &'static str,
+//! }
+//! assert_eq!(Context { name: "Bob" }.to_string(), "Hello, Bob!\nBye!\n");
+//! ```
+//!
+//! Included templates are parsed separately, so text at the end of an
+//! included template is never combined with a delimiter after the include:
+//!
+//! ```
+//! #[derive(boilerplate::Boilerplate)]
+//! #[boilerplate(text = "{% include \"partials/brace.txt\" %}{{ self.0 }}")]
+//! struct Context(&'static str);
+//! assert_eq!(Context("foo").to_string(), "{foo");
+//! ```
+//!
 //! ### Loops
 //!
 //! ```
@@ -303,8 +550,13 @@
 //!
 //! ### Escaping
 //!
-//! If the template file path ends with an `html`, `htm`, or `xml` extension,
-//! escaping is enabled.
+//! If the template file path ends with an `html`, `htm`, `xml`, `css`, `js`,
+//! `json`, `sh`, or `tex` extension, escaping is enabled, using the escapers
+//! in `boilerplate::escaper`.
+//!
+//! Templates with a `css`, `js`, `json`, `sh`, or `tex` extension were not
+//! escaped in earlier versions. To keep the old behavior, add
+//! `#[boilerplate(escape = false)]`.
 //!
 //! ```
 //! #[derive(boilerplate::Boilerplate)]
@@ -330,6 +582,219 @@
 //! assert_eq!(ContextHtml("&").to_string(), "&\n");
 //! ```
 //!
+//! In `html` and `htm` templates, the HTML surrounding each interpolation is
+//! scanned at compile time, and the interpolation is escaped for its context.
+//! Interpolations at the start of URL attribute values, like `href` and `src`,
+//! are replaced with `about:invalid#boilerplate` if they have a scheme other
+//! than `http`, `https`, or `mailto`:
+//!
+//! ```
+//! #[derive(boilerplate::Boilerplate)]
+//! #[boilerplate(text = "<a href=\"{{ self.0 }}\">link</a>")]
+//! struct LinkHtml(&'static str);
+//! assert_eq!(
+//!   LinkHtml("https://example.com/?a=b&c=d").to_string(),
+//!   "<a href=\"https://example.com/?a=b&amp;c=d\">link</a>",
+//! );
+//! assert_eq!(
+//!   LinkHtml("javascript:alert(1)").to_string(),
+//!   "<a href=\"about:invalid#boilerplate\">link</a>",
+//! );
+//! ```
+//!
+//! Interpolations in string literals in `<script>` and `<style>` elements are
+//! escaped for JavaScript and CSS strings:
+//!
+//! ```
+//! #[derive(boilerplate::Boilerplate)]
+//! #[boilerplate(text = "<script>let name = \"{{ self.0 }}\";</script>")]
+//! struct ScriptHtml(&'static str);
+//! assert_eq!(
+//!   ScriptHtml("</script>").to_string(),
+//!   r#"<script>let name = "\u003c/script\u003e";</script>"#,
+//! );
+//! ```
+//!
+//! Interpolations in contexts which cannot be safely escaped, like tag and
+//! attribute names, unquoted attribute values, event handler and `style`
+//! attributes, and `<script>` and `<style>` elements outside of string
+//! literals, are compile errors:
+//!
+//! ```compile_fail
+//! #[derive(boilerplate::Boilerplate)]
+//! #[boilerplate(text = "<button onclick=\"{{ self.0 }}\">")]
+//! struct ButtonHtml(&'static str);
+//! ```
+//!
+//! ```compile_fail
+//! #[derive(boilerplate::Boilerplate)]
+//! #[boilerplate(text = "<div class={{ self.0 }}>")]
+//! struct DivHtml(&'static str);
+//! ```
+//!
+//! Values which are trusted with `Trusted` or the `trusted` filter, or escaped
+//! with `Escaped`, are allowed in any context, since they are not escaped by
+//! the template:
+//!
+//! ```
+//! use boilerplate::Trusted;
+//!
+//! #[derive(boilerplate::Boilerplate)]
+//! #[boilerplate(text = "<input {{ Trusted(self.0) }}>
+//! <script>let data = {{ self.1 |> trusted }};</script>
+//! ")]
+//! struct FormHtml(&'static str, &'static str);
+//!
+//! assert_eq!(
+//!   FormHtml("disabled", "[1, 2]").to_string(),
+//!   "<input disabled>\n<script>let data = [1, 2];</script>\n",
+//! );
+//! ```
+//!
+//! `xml` templates are escaped as text, regardless of context.
+//!
+//! In `css`, `js`, and `json` templates, interpolations are escaped for
+//! inclusion in string literals, and in `sh` templates, interpolations are
+//! quoted as single words:
+//!
+//! ```
+//! #[derive(boilerplate::Boilerplate)]
+//! #[boilerplate(text = "{\"name\": \"{{ self.0 }}\"}")]
+//! struct PayloadJson(&'static str);
+//! assert_eq!(PayloadJson("\"Bob\"").to_string(), r#"{"name": "\"Bob\""}"#);
+//! ```
+//!
+//! ```
+//! #[derive(boilerplate::Boilerplate)]
+//! #[boilerplate(text = "rm {{ self.0 }}\n")]
+//! struct CleanSh(&'static str);
+//! assert_eq!(CleanSh("it's; rm -rf /").to_string(), "rm 'it'\\''s; rm -rf /'\n");
+//! ```
+//!
+//! The `Escaped` wrapper escapes a value with an escaper other than the
+//! template's:
+//!
+//! ```
+//! use boilerplate::{Escaped, escaper::Tex};
+//!
+//! #[derive(boilerplate::Boilerplate)]
+//! #[boilerplate(text = "{{ Escaped(Tex, self.0) }}")]
+//! struct ReportHtml(&'static str);
+//! assert_eq!(ReportHtml("100% & <more>").to_string(), r"100\% \& <more>");
+//! ```
+//!
+//! Escaping can be enabled or disabled regardless of extension with
+//! `escape = true` and `escape = false`. Templates without an escaping
+//! extension are HTML-escaped as text when `escape = true`:
+//!
+//! ```
+//! #[derive(boilerplate::Boilerplate)]
+//! #[boilerplate(escape = true, text = "{{ self.0 }}")]
+//! struct Context(&'static str);
+//! assert_eq!(Context("<br>").to_string(), "&lt;br&gt;");
+//! ```
+//!
+//! ```
+//! #[derive(boilerplate::Boilerplate)]
+//! #[boilerplate(escape = false, text = "{{ self.0 }}")]
+//! struct ContextHtml(&'static str);
+//! assert_eq!(ContextHtml("<br>").to_string(), "<br>");
+//! ```
+//!
+//! The escaping for an extension can be used regardless of the template's
+//! extension with `escape = "extension"`:
+//!
+//! ```
+//! #[derive(boilerplate::Boilerplate)]
+//! #[boilerplate(escape = "html", text = "<p>{{ self.0 }}</p>")]
+//! struct Context(&'static str);
+//! assert_eq!(Context("<br>").to_string(), "<p>&lt;br&gt;</p>");
+//! ```
+//!
+//! Custom escapers implement `boilerplate::Escaper`, and are used with
+//! `escape = path::to::Escaper`:
+//!
+//! ```
+//! use {
+//!   boilerplate::Escaper,
+//!   core::fmt::{self, Write},
+//! };
+//!
+//! struct Shout;
+//!
+//! impl Escaper for Shout {
+//!   fn escape(&self, f: &mut dyn Write, s: &str) -> fmt::Result {
+//!     for c in s.chars() {
+//!       f.write_char(c.to_ascii_uppercase())?;
+//!     }
+//!     Ok(())
+//!   }
+//! }
+//!
+//! #[derive(boilerplate::Boilerplate)]
+//! #[boilerplate(escape = Shout, text = "{{ self.0 }}!")]
+//! struct Context(&'static str);
+//! assert_eq!(Context("hello").to_string(), "HELLO!");
+//! ```
+//!
+//! ### Filters
+//!
+//! Filters are applied to interpolations with `|>`, and may take arguments:
+//!
+//! ```
+//! #[derive(boilerplate::Boilerplate)]
+//! #[boilerplate(text = "{{ self.0 |> trim |> upper }} {{ self.0 |> truncate(4) }}")]
+//! struct Context(&'static str);
+//! assert_eq!(Context(" hello ").to_string(), "HELLO  he…");
+//! ```
+//!
+//! The built-in filters are `upper`, `lower`, `trim`, `truncate`, and the
+//! escaping filters `css`, `html`, `js`, `json`, `sh`, `tex`, `url`, and
+//! `urlencode`, which are documented in `boilerplate::filters`. The output
+//! of filters is escaped by the template's escaper, unless the last filter
+//! is `trusted`:
+//!
+//! ```
+//! #[derive(boilerplate::Boilerplate)]
+//! #[boilerplate(
+//!   text = "<a href=\"/search?q={{ self.0 |> urlencode }}\">\
+//!     {{ self.1 |> trusted }}</a>"
+//! )]
+//! struct LinkHtml(&'static str, &'static str);
+//! assert_eq!(
+//!   LinkHtml("cats & dogs", "<b>search</b>").to_string(),
+//!   "<a href=\"/search?q=cats%20%26%20dogs\"><b>search</b></a>",
+//! );
+//! ```
+//!
+//! Other filters are called from a `filters` module in scope where the
+//! template is used, with the value and any arguments:
+//!
+//! ```
+//! mod filters {
+//!   use core::fmt::Display;
+//!
+//!   pub fn repeat(value: impl Display, n: usize) -> String {
+//!     value.to_string().repeat(n)
+//!   }
+//! }
+//!
+//! #[derive(boilerplate::Boilerplate)]
+//! #[boilerplate(text = "{{ self.0 |> repeat(3) |> upper }}")]
+//! struct Context(&'static str);
+//! assert_eq!(Context("ha").to_string(), "HAHAHA");
+//! ```
+//!
+//! Since `|>` is not a Rust operator, interpolations without it, like
+//! `{{ a | b }}`, are always ordinary Rust expressions:
+//!
+//! ```
+//! #[derive(boilerplate::Boilerplate)]
+//! #[boilerplate(text = "{{ self.0 | self.1 }} {{ self.0 | self.1 |> json }}")]
+//! struct Context(u8, u8);
+//! assert_eq!(Context(1, 2).to_string(), "3 3");
+//! ```
+//!
 //! ### Auto-indenting
 //!
 //! When an interpolation appears on a line preceded only by whitespace, that
@@ -393,6 +858,12 @@
 //! );
 //! ```
 //!
+//! Interpolated values are indented and trimmed by `boilerplate::Formatter`,
+//! which can also be used directly. Types which implement
+//! `boilerplate::Format`, instead of `Display`, receive the indent and
+//! escaper, so that they can write nested output which cooperates with
+//! auto-indenting.
+//!
 //! ### Generics
 //!
 //! Context types may have lifetimes and generics;
@@ -406,6 +877,144 @@
 //! assert_eq!(Context { content: &100 }.to_string(), "100\n");
 //! ```
 //!
+//! ### Enums
+//!
+//! `Boilerplate` can be derived on enums, in which case each variant has its
+//! own template, set with `#[boilerplate(filename = "...")]` or
+//! `#[boilerplate(text = "...")]` on the variant. The template filename
+//! defaults to the variant name, for example, `IndexHtml` is rendered with
+//! `templates/index.html`. Attributes on the enum, other than `filename` and
+//! `text`, apply to all variants.
+//!
+//! In a variant's template, `self` is a struct with the variant's fields,
+//! borrowed:
+//!
+//! ```
+//! #[derive(boilerplate::Boilerplate)]
+//! enum Notification {
+//!   #[boilerplate(text = "{{ self.0 }} liked your post")]
+//!   Like(&'static str),
+//!   #[boilerplate(text = "{{ self.name }} said {{ self.message }}")]
+//!   Reply { message: String, name: &'static str },
+//!   #[boilerplate(text = "Welcome!")]
+//!   Welcome,
+//! }
+//!
+//! assert_eq!(Notification::Like("Bob").to_string(), "Bob liked your post");
+//! assert_eq!(
+//!   Notification::Reply { message: "hi".into(), name: "Alice" }.to_string(),
+//!   "Alice said hi",
+//! );
+//! assert_eq!(Notification::Welcome.to_string(), "Welcome!");
+//! ```
+//!
+//! Enums implement `Display` and the `Boilerplate` trait, which render the
+//! template of the enum's variant. The enum's `Boilerplate::TEXT` contains
+//! the text blocks of each variant's template in turn, and its
+//! `Boilerplate::SIZE_HINT` is the largest of the variants' size hints:
+//!
+//! ```
+//! use boilerplate::Boilerplate;
+//!
+//! #[derive(Boilerplate)]
+//! enum Greeting {
+//!   #[boilerplate(text = "Hello, {{ self.0 }}!")]
+//!   Hello(&'static str),
+//!   #[boilerplate(text = "Goodbye, {{ self.0 }}!")]
+//!   Goodbye(&'static str),
+//! }
+//!
+//! assert_eq!(Greeting::TEXT, ["Hello, ", "!", "Goodbye, ", "!"]);
+//! assert_eq!(Greeting::Goodbye("Bob").render().unwrap(), "Goodbye, Bob!");
+//! ```
+//!
+//! Since an enum has no single template, its `Boilerplate::TEMPLATE` is
+//! empty, and it cannot be reloaded. Enums whose variants all have HTML
+//! templates implement `HtmlSafe`.
+//!
+//! ### Errors in Template Code
+//!
+//! Interpolations which are not valid Rust expressions are reported with the
+//! line and column of the interpolation, prefixed with the template path for
+//! templates read from files:
+//!
+//! ```text
+//! error: /home/user/foo/templates/index.html:42:5: unexpected end of input, expected an expression
+//! ```
+//!
+//! Other errors in template code, like type errors, are reported by the Rust
+//! compiler. For templates in string literals, they point to the literal. For
+//! templates read from files, they point to the `filename` or `path`
+//! attribute, and interpolations and the expressions at the start of code
+//! blocks, like the condition of an `if` or the iterator of a `for` loop, are
+//! wrapped in macros named after the template's path and the line of the
+//! expression, which the compiler names in the error:
+//!
+//! ```text
+//! error[E0599]: no method named `foo` found for struct `String` in the current scope
+//!  --> src/main.rs:2:26
+//!   |
+//! 2 | #[boilerplate(filename = "index.html")]
+//!   |                          ^^^^^^^^^^^^
+//!   |
+//!   = note: this error originates in the macro `templates_index_html_42` …
+//! ```
+//!
+//! ### Fallible Templates
+//!
+//! Template code normally cannot use `?`, since templates are rendered by
+//! functions which return `core::fmt::Result`. With
+//! `#[boilerplate(error = Error)]`, templates are rendered by an inherent
+//! `try_render` method which returns `Result<String, Error>`, and template
+//! code may use `?` on results whose errors convert into `Error`. `Error`
+//! must implement `From<core::fmt::Error>`, so that write errors can be
+//! returned:
+//!
+//! ```
+//! use std::{collections::BTreeMap, fmt};
+//!
+//! #[derive(Debug, PartialEq)]
+//! enum Error {
+//!   Fmt,
+//!   Missing(&'static str),
+//! }
+//!
+//! impl From<fmt::Error> for Error {
+//!   fn from(_: fmt::Error) -> Self {
+//!     Self::Fmt
+//!   }
+//! }
+//!
+//! fn lookup(prices: &BTreeMap<&str, u32>, item: &'static str) -> Result<u32, Error> {
+//!   prices.get(item).copied().ok_or(Error::Missing(item))
+//! }
+//!
+//! #[derive(boilerplate::Boilerplate)]
+//! #[boilerplate(error = Error, text = "%% for item in self.items {
+//! {{ item }}: {{ lookup(&self.prices, item)? }}
+//! %% }
+//! ")]
+//! struct Receipt {
+//!   items: &'static [&'static str],
+//!   prices: BTreeMap<&'static str, u32>,
+//! }
+//!
+//! let prices = BTreeMap::from([("apple", 1), ("pear", 2)]);
+//!
+//! assert_eq!(
+//!   Receipt { items: &["apple", "pear"], prices: prices.clone() }.try_render(),
+//!   Ok("apple: 1\npear: 2\n".into()),
+//! );
+//!
+//! assert_eq!(
+//!   Receipt { items: &["apple", "kiwi"], prices }.try_render(),
+//!   Err(Error::Missing("kiwi")),
+//! );
+//! ```
+//!
+//! The `Display` implementation and `Boilerplate` trait methods return
+//! `core::fmt::Error` if template code returns an error.
+//!
 //! ### Axum Integration
 //!
 //! When the `axum` feature is enabled, templates will be provided with an
@@ -475,6 +1084,32 @@
 //! GuessHtml {}.into_response();
 //! ```
 //!
+//! Templates with an error type respond with `500 Internal Server Error` if
+//! template code returns an error:
+//!
+//! ```
+//! use {axum::{http::StatusCode, response::IntoResponse}, std::fmt};
+//!
+//! struct Error;
+//!
+//! impl From<fmt::Error> for Error {
+//!   fn from(_: fmt::Error) -> Self {
+//!     Self
+//!   }
+//! }
+//!
+//! #[derive(boilerplate::Boilerplate)]
+//! #[boilerplate(axum = true, error = Error, text = "{{ self.0.ok_or(Error)? }}")]
+//! struct Count(Option<u32>);
+//!
+//! assert_eq!(Count(Some(1)).into_response().status(), StatusCode::OK);
+//!
+//! assert_eq!(
+//!   Count(None).into_response().status(),
+//!   StatusCode::INTERNAL_SERVER_ERROR,
+//! );
+//! ```
+//!
 //! ### Reloading Templates
 //!
 //! When the `reload` feature is enabled, templates support a limited form of
@@ -540,7 +1175,7 @@
 //!   let incompatible_template = "Goodbye, {{self.first";
 //!   assert_eq!(
 //!     context.reload(incompatible_template).err().unwrap().to_string(),
-//!     "failed to parse new template: unmatched `{{`",
+//!     "failed to parse new template: unmatched `{{` at line 1, column 10",
 //!   );
 //! }
 //! ```
@@ -576,6 +1211,81 @@
 //! }
 //! ```
 //!
+//! Comments can be added, changed, and removed:
+//!
+//! ```
+//! #[cfg(feature = "reload")]
+//! {
+//!   // import the `Boilerplate` trait for the `reload` method
+//!   use boilerplate::Boilerplate;
+//!
+//!   #[derive(Boilerplate)]
+//!   #[boilerplate(text = "Hello, {{# name #}}{{self.name}}!")]
+//!   struct Context {
+//!     name: &'static str,
+//!   }
+//!
+//!   let context = Context { name: "Bob" };
+//!
+//!   let compatible_template = "Hello, {{self.name}}! {{# greeting #}}";
+//!   assert_eq!(context.reload(compatible_template).unwrap().to_string(), "Hello, Bob! ");
+//! }
+//! ```
+//!
+//! New templates are parsed with the original template's delimiters:
+//!
+//! ```
+//! #[cfg(feature = "reload")]
+//! {
+//!   // import the `Boilerplate` trait for the `reload` method
+//!   use boilerplate::Boilerplate;
+//!
+//!   #[derive(Boilerplate)]
+//!   #[boilerplate(delimiters(interpolation = "<%= %>"), text = "Hello, <%= self.name %>!")]
+//!   struct Context {
+//!     name: &'static str,
+//!   }
+//!
+//!   let context = Context { name: "Bob" };
+//!
+//!   let compatible_template = "{{ Goodbye }}, <%= self.name %>!";
+//!   assert_eq!(
+//!     context.reload(compatible_template).unwrap().to_string(),
+//!     "{{ Goodbye }}, Bob!",
+//!   );
+//!
+//!   let incompatible_template = "Goodbye, <%= self.first %>!";
+//!   assert_eq!(
+//!     context.reload(incompatible_template).err().unwrap().to_string(),
+//!     "template blocks are not compatible: <%= self.first %> != <%= self.name %>",
+//!   );
+//! }
+//! ```
+//!
+//! Includes in new templates are read from the `templates` directory:
+//!
+//! ```
+//! #[cfg(feature = "reload")]
+//! {
+//!   // import the `Boilerplate` trait for the `reload` method
+//!   use boilerplate::Boilerplate;
+//!
+//!   #[derive(Boilerplate)]
+//!   #[boilerplate(text = "{% include \"partials/greeting.txt\" %}")]
+//!   struct Context {
+//!     name: &'static str,
+//!   }
+//!
+//!   let context = Context { name: "Bob" };
+//!
+//!   let compatible_template = "<p>{% include \"partials/greeting.txt\" %}</p>";
+//!   assert_eq!(
+//!     context.reload(compatible_template).unwrap().to_string(),
+//!     "<p>Hello, Bob!</p>",
+//!   );
+//! }
+//! ```
+//!
 //! Text between code blocks can be changed:
 //!
 //! ```
@@ -671,6 +1381,85 @@
 //! assert_eq!(output, "Foo was true!\nPretty good: yassss\n");
 //! ```
 //!
+//! Templates passed to `boilerplate` are not escaped by default. Escaping can
+//! be enabled by passing the extension whose escaping should be used before
+//! the template, or with `escape = ...`, which takes the same values as
+//! `#[boilerplate(escape = ...)]` on the derive macro, as well as an extension
+//! in a string:
+//!
+//! ```
+//! use boilerplate::boilerplate;
+//!
+//! let name = "<script>";
+//!
+//! assert_eq!(
+//!   boilerplate!(html, "<p title=\"{{ name }}\">{{ name }}</p>"),
+//!   "<p title=\"&lt;script&gt;\">&lt;script&gt;</p>",
+//! );
+//!
+//! assert_eq!(
+//!   boilerplate!(escape = "json", "{\"name\": \"{{ name }}\"}"),
+//!   "{\"name\": \"\\u003cscript\\u003e\"}",
+//! );
+//! ```
+//!
+//! Templates can also be read from files in the `templates` directory with
+//! `file = "path"`, and are escaped according to their extension:
+//!
+//! ```
+//! use boilerplate::boilerplate;
+//!
+//! let title = "Tom & Jerry";
+//!
+//! assert_eq!(
+//!   boilerplate!(file = "card.html"),
+//!   "<div class=\"card\">\n  <h2>Tom &amp; Jerry</h2>\n</div>\n",
+//! );
+//! ```
+//!
+//! `boilerplate_display` takes the same arguments as `boilerplate`, but
+//! instead of rendering the template to a `String`, returns a value which
+//! implements `Display`, and renders the template each time it is displayed.
+//! The returned value can be written to a stream, or interpolated into another
+//! template, without allocating:
+//!
+//! ```
+//! use boilerplate::{boilerplate, boilerplate_display};
+//!
+//! let items = ["foo", "<bar>"];
+//!
+//! let list = boilerplate_display!(html, "<ul>
+//! %% for item in items {
+//!   <li>{{ item }}</li>
+//! %% }
+//! </ul>
+//! ");
+//!
+//! assert_eq!(
+//!   boilerplate!(html, "<div>{{ list }}</div>"),
+//!   "<div><ul>\n  <li>foo</li>\n  <li>&lt;bar&gt;</li>\n</ul>\n</div>",
+//! );
+//! ```
+//!
+//! Like a `move` closure, the returned value captures the variables it uses by
+//! value, so it can be returned from functions which take references:
+//!
+//! ```
+//! use {boilerplate::boilerplate_display, std::fmt::Display};
+//!
+//! fn greet(name: &str) -> impl Display + '_ {
+//!   boilerplate_display!("Hello, {{ name }}!")
+//! }
+//!
+//! assert_eq!(greet("Bob").to_string(), "Hello, Bob!");
+//! ```
+//!
+//! To keep using a variable which is not `Copy` after creating the template,
+//! shadow it with a reference first, for example with `let items = &items;`.
+//!
+//! HTML templates returned by `boilerplate_display` implement `HtmlSafe`, and
+//! so are not escaped again when interpolated into other HTML templates.
+//!
 //! Nesting Templates
 //! -----------------
 //!
@@ -690,16 +1479,16 @@
 //! ```
 //!
 //! This is especially useful when generating multiple HTML pages with unique
-//! content, but with headers and footers that are common to all pages. Note
-//! the use of `boilerplate::Trusted` to prevent escaping the inner HTML:
+//! content, but with headers and footers that are common to all pages. HTML
+//! templates implement `boilerplate::HtmlSafe`, and are not escaped again when
+//! interpolated into the text of other HTML templates. Note the use of
+//! `HtmlSafe` as a supertrait, so that `self.0` is known to be safe in the
+//! generic outer template:
 //!
 //! ```
-//! use {
-//!   boilerplate::Trusted,
-//!   std::fmt::Display,
-//! };
+//! use boilerplate::HtmlSafe;
 //!
-//! trait Page: Display {
+//! trait Page: HtmlSafe {
 //!   fn title(&self) -> &str;
 //! }
 //!
@@ -710,7 +1499,7 @@
 //!     <title>{{ self.0.title() }}</title>
 //!   </head>
 //!   <body>
-//!     {{ Trusted(&self.0) }}
+//!     {{ self.0 }}
 //!   </body>
 //! </html>
 //! ")]
@@ -743,32 +1532,114 @@
 //! </html>
 //! ");
 //! ```
+//!
+//! Template Inheritance
+//! --------------------
+//!
+//! Templates can also share common content by extending another template. A
+//! template which starts with `{% extends "path" %}` is replaced by the
+//! template at `path`, relative to the `templates` directory, with each
+//! `{% block name %}...{% endblock %}` in that template replaced by the block
+//! with the same name in the extending template. Blocks which aren't replaced
+//! keep their original contents, and content outside of blocks in the
+//! extending template is ignored.
+//!
+//! Given `templates/base.html`:
+//!
+//! ```html
+//! <!doctype html>
+//! <html>
+//!   <head>
+//!     <title>{% block title %}{% endblock %}</title>
+//!   </head>
+//!   <body>
+//!     {% block body %}
+//!     {% endblock %}
+//!   </body>
+//! </html>
+//! ```
+//!
+//! The page above can be written as:
+//!
+//! ```
+//! #[derive(boilerplate::Boilerplate)]
+//! #[boilerplate(text = "{% extends \"base.html\" %}
+//! {% block title %}awesome page{% endblock %}
+//! {% block body %}
+//! <div>
+//!   {{ self.0 }}
+//! </div>
+//! {% endblock %}
+//! ")]
+//! struct PageHtml(&'static str);
+//!
+//! assert_eq!(
+//!   PageHtml("awesome & content").to_string(),
+//!   "<!doctype html>
+//! <html>
+//!   <head>
+//!     <title>awesome page</title>
+//!   </head>
+//!   <body>
+//!     <div>
+//!       awesome &amp; content
+//!     </div>
+//!   </body>
+//! </html>
+//! ");
+//! ```
+//!
+//! Block directives which are alone on their line are removed along with
+//! their line, and blocks which replace a block that is preceded only by
+//! whitespace on its line are indented with that whitespace, like includes.
+//! Extended templates may themselves extend other templates, and blocks may be
+//! nested. `extends`, `block`, and `endblock` may also be written as code
+//! lines, for example `%% block body`.
 
-#![cfg_attr(not(feature = "reload"), no_std)]
+#![cfg_attr(not(feature = "std"), no_std)]
 
-#[cfg(not(feature = "reload"))]
 extern crate alloc;
 
 pub use {
-  self::{boilerplate::Boilerplate, trusted::Trusted},
-  boilerplate_macros::{Boilerplate, boilerplate},
+  self::{
+    boilerplate::Boilerplate, escaped::Escaped, escaper::Escaper, format::Format,
+    formatter::Formatter, html_safe::HtmlSafe, trusted::Trusted,
+  },
+  boilerplate_macros::{Boilerplate, boilerplate, boilerplate_display},
 };
 
 #[cfg(feature = "reload")]
 pub use {
   self::reload::{Error, Reload},
-  boilerplate_parser::Token,
+  boilerplate_parser::{Delimiters, Token},
 };
 
 #[doc(hidden)]
-pub use self::{format::Format, formatter::Formatter};
+pub use self::{
+  display_fn::DisplayFn,
+  html_safe::{Interpolate, InterpolateHtmlSafe, Interpolation},
+};
 
-use core::fmt::{self, Display, Write};
+#[cfg(feature = "std")]
+use {self::io_writer::IoWriter, std::io};
+
+use {
+  alloc::string::{String, ToString},
+  core::fmt::{self, Display, Write},
+};
 
 mod boilerplate;
+mod display_fn;
+mod escaped;
+pub mod escaper;
+pub mod filters;
 mod format;
 mod formatter;
+mod html_safe;
 mod trusted;
 
+#[cfg(feature = "std")]
+mod io_writer;
+
 #[cfg(feature = "reload")]
 mod reload;
@@ -4,15 +4,26 @@ use super::*;
 pub struct Trusted<T: Display>(pub T);
 
 impl<T: Display> Format for T {
-  fn format(&self, f: &mut fmt::Formatter, indent: &'static str, trim: bool) -> fmt::Result {
-    let mut f = Formatter::new(f, true, indent, trim);
-    write!(f, "{self}")
+  fn format(
+    &self,
+    f: &mut dyn Write,
+    escaper: &dyn Escaper,
+    indent: &str,
+    trim: bool,
+  ) -> fmt::Result {
+    escaper.format(&mut Formatter::new(f).indent(indent).trim(trim), self)
   }
 }
 
 impl<T: Display> Format for Trusted<T> {
-  fn format(&self, f: &mut fmt::Formatter, indent: &'static str, trim: bool) -> fmt::Result {
-    let mut f = Formatter::new(f, false, indent, trim);
+  fn format(
+    &self,
+    f: &mut dyn Write,
+    _escaper: &dyn Escaper,
+    indent: &str,
+    trim: bool,
+  ) -> fmt::Result {
+    let mut f = Formatter::new(f).indent(indent).trim(trim);
     write!(f, "{}", self.0)
   }
 }
@@ -22,14 +22,19 @@ endif
 syn include @rustTop syntax/rust.vim
 unlet! b:current_syntax
 
-syn cluster boilerplateRegions contains=boilerplateCode,boilerplateCodeLine,boilerplateInterpolation,boilerplateInterpolationLine
+syn cluster boilerplateRegions contains=boilerplateCode,boilerplateCodeLine,boilerplateComment,boilerplateCommentLine,boilerplateEscape,boilerplateInterpolation,boilerplateInterpolationLine
 
 syn region boilerplateCode              matchgroup=boilerplateDelimiter start="{%"   end="%}" contains=@rustTop containedin=ALLBUT,@boilerplateRegions keepend
 syn region boilerplateInterpolation     matchgroup=boilerplateDelimiter start="{{"   end="}}" contains=@rustTop containedin=ALLBUT,@boilerplateRegions keepend
 syn region boilerplateCodeLine          matchgroup=boilerplateDelimiter start="%%"   end="$"  contains=@rustTop containedin=ALLBUT,@boilerplateRegions keepend oneline
 syn region boilerplateInterpolationLine matchgroup=boilerplateDelimiter start="\$\$" end="$"  contains=@rustTop containedin=ALLBUT,@boilerplateRegions keepend oneline
+syn region boilerplateComment           start="{{#"  end="#}}"                                containedin=ALLBUT,@boilerplateRegions keepend
+syn region boilerplateCommentLine       start="\$\$#" end="$"                                  containedin=ALLBUT,@boilerplateRegions keepend oneline
+syn match  boilerplateEscape            "\({{#\|\$\$#\|{%\|{{\|%%\|\$\$\)\1" containedin=ALLBUT,@boilerplateRegions
 
-hi def link boilerplateDelimiter PreProc
+hi def link boilerplateDelimiter   PreProc
+hi def link boilerplateComment     Comment
+hi def link boilerplateCommentLine Comment
 
 let b:current_syntax = 'boilerplate'
 
@@ -32,6 +32,9 @@ let s:lines = [
 \ '  Byte $$ self.byte',
 \ '  {% } %}',
 \ '</body>',
+\ '{{# note #}}',
+\ '$$# note',
+\ '{{{{ literal }}',
 \ ]
 
 enew
@@ -69,6 +72,15 @@ call assert_match('^rust', s:name(4, 6), 'let is rust inside code line')
 call assert_true(s:has(s:stack(5, 11), 'boilerplateInterpolationLine'), 'interpolation line region')
 call assert_match('^rust', s:name(5, 11), 'self is rust inside interpolation line')
 
+" comments `{{# ... #}}` and `$$# ...` are comments, not Rust
+call assert_equal('boilerplateComment', s:name(8, 4), 'comment block')
+call assert_equal('boilerplateCommentLine', s:name(9, 4), 'comment line')
+call assert_false(s:has(s:stack(8, 4), '^rust'), 'no rust inside comment block')
+
+" doubled delimiters `{{{{` do not open blocks
+call assert_equal('boilerplateEscape', s:name(10, 2), 'doubled delimiter')
+call assert_false(s:has(s:stack(10, 7), '^\(rust\|boilerplate\)'), 'no block after doubled delimiter')
+
 " blocks are italicized with a background by default (Neovim only)
 if has('nvim')
   let s:hl = nvim_get_hl(0, {'name': 'boilerplateInterpolation'})